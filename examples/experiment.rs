@@ -10,10 +10,7 @@ fn main() -> anyhow::Result<()> {
 
     let experiment = experiment::ExperimentConfig {
         artifact_directory: "artifacts/experiment".into(),
-        dataset: dataset::SimpleNerfDatasetConfig {
-            points_per_ray: 20,
-            distance_range: 2.0..6.0,
-        },
+        dataset: dataset::SimpleNerfDatasetConfig::new(20, 2.0..6.0),
         dataset_file_path_or_url: "resources/lego-tiny/data.npz".into(),
         epoch_count: 10000,
         learning_rate: 1e-3,
@@ -22,7 +19,11 @@ fn main() -> anyhow::Result<()> {
                 hidden_size: 256,
                 input_encoder: encoder::PositionalEncoderConfig {
                     encoding_factor: 10,
+                    include_input: true,
                 },
+                direction_encoder: None,
+                layer_count: 8,
+                skip_index: 5,
             },
         },
         train_ratio: 0.8,
@@ -0,0 +1,105 @@
+use super::*;
+
+use burn::data::dataloader::batcher::Batcher;
+
+#[derive(Clone, Debug)]
+pub struct SimpleNerfBatcher<B: Backend> {
+    device: B::Device,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimpleNerfBatch<B: Backend> {
+    pub depths: Option<Tensor<B, 4>>,
+    pub directions: Tensor<B, 5>,
+    pub distances: Tensor<B, 5>,
+    pub images: Tensor<B, 4>,
+    pub intervals: Tensor<B, 5>,
+    pub masks: Option<Tensor<B, 4>>,
+    pub positions: Tensor<B, 5>,
+}
+
+impl<B: Backend> SimpleNerfBatcher<B> {
+    pub fn new(device: B::Device) -> Self {
+        Self {
+            device,
+        }
+    }
+}
+
+impl<B: Backend> Batcher<SimpleNerfData, SimpleNerfBatch<B>>
+    for SimpleNerfBatcher<B>
+{
+    // NOTE: The depths and masks are only batched if every item has one
+    fn batch(
+        &self,
+        items: Vec<SimpleNerfData>,
+    ) -> SimpleNerfBatch<B> {
+        let inputs = items
+            .into_iter()
+            .map(|item| item.into_input::<B>(&self.device))
+            .collect::<Vec<_>>();
+
+        let masks = inputs
+            .iter()
+            .map(|input| input.mask.clone())
+            .collect::<Option<Vec<_>>>()
+            .map(|masks| Tensor::stack(masks, 0));
+        let depths = inputs
+            .iter()
+            .map(|input| input.depth.clone())
+            .collect::<Option<Vec<_>>>()
+            .map(|depths| Tensor::stack(depths, 0));
+
+        SimpleNerfBatch {
+            depths,
+            directions: Tensor::stack(
+                inputs.iter().map(|input| input.directions.clone()).collect(),
+                0,
+            ),
+            distances: Tensor::stack(
+                inputs.iter().map(|input| input.distances.clone()).collect(),
+                0,
+            ),
+            images: Tensor::stack(
+                inputs.iter().map(|input| input.image.clone()).collect(),
+                0,
+            ),
+            intervals: Tensor::stack(
+                inputs.iter().map(|input| input.intervals.clone()).collect(),
+                0,
+            ),
+            masks,
+            positions: Tensor::stack(
+                inputs.into_iter().map(|input| input.positions).collect(),
+                0,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::*;
+    use super::*;
+
+    type Backend = burn::backend::Wgpu;
+
+    #[test]
+    fn simple_nerf_dataset_batching() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let batch = SimpleNerfBatcher::<Backend>::new(device)
+            .batch(vec![dataset.get(0).unwrap(), dataset.get(1).unwrap()]);
+
+        assert_eq!(batch.directions.dims(), [2, 100, 100, 7, 3]);
+        assert_eq!(batch.distances.dims(), [2, 100, 100, 7, 1]);
+        assert_eq!(batch.images.dims(), [2, 100, 100, 3]);
+        assert_eq!(batch.intervals.dims(), [2, 100, 100, 7, 1]);
+        assert_eq!(batch.positions.dims(), [2, 100, 100, 7, 3]);
+        assert!(batch.depths.is_none());
+        assert!(batch.masks.is_none());
+    }
+}
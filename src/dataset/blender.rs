@@ -0,0 +1,189 @@
+use super::*;
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct BlenderTransforms {
+    camera_angle_x: f64,
+    frames: Vec<BlenderFrame>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlenderFrame {
+    file_path: String,
+    transform_matrix: Vec<Vec<f32>>,
+}
+
+impl SimpleNerfDatasetConfig {
+    pub fn init_from_blender_dir<B: Backend>(
+        &self,
+        directory: impl AsRef<Path>,
+        split: &str,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        let directory = directory.as_ref();
+
+        let transforms: BlenderTransforms =
+            serde_json::from_reader(io::BufReader::new(File::open(
+                directory.join(format!("transforms_{}.json", split)),
+            )?))?;
+        let image_count = transforms.frames.len();
+        if image_count == 0 {
+            return Err(DatasetError::Parse(format!(
+                "No frames are listed in the split {:?}",
+                split
+            )));
+        }
+
+        let mut images = vec![];
+        let mut poses = vec![];
+        let mut size = None;
+        let mut original_sizes = vec![];
+        for frame in transforms.frames.iter() {
+            let mut image_path = directory.join(&frame.file_path);
+            if image_path.extension().is_none() {
+                image_path.set_extension("png");
+            }
+            if !image_path.is_file() {
+                return Err(DatasetError::Io(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Frame image is missing: {:?}", image_path),
+                )));
+            }
+
+            let image = image::open(&image_path)
+                .map_err(|error| DatasetError::Parse(error.to_string()))?
+                .into_rgba32f();
+            let (width, height) = image.dimensions();
+            original_sizes.push((width as usize, height as usize));
+            let image = resize_image(image, self.resize_to);
+            let dimensions = image.dimensions();
+            if *size.get_or_insert(dimensions) != dimensions {
+                return Err(DatasetError::ShapeMismatch {
+                    name: format!("{:?}", image_path),
+                    expected: format!(
+                        "[{}, {}, 4]",
+                        size.unwrap().1,
+                        size.unwrap().0
+                    ),
+                    got: vec![dimensions.1 as usize, dimensions.0 as usize, 4],
+                });
+            }
+            images.extend(image.into_raw());
+
+            let matrix = &frame.transform_matrix;
+            if !(matrix.len() == 3 || matrix.len() == 4)
+                || matrix.iter().any(|row| row.len() != 4)
+            {
+                return Err(DatasetError::Parse(format!(
+                    "Transform matrix is neither 3x4 nor 4x4: {:?}",
+                    frame.file_path
+                )));
+            }
+            poses.extend(matrix.iter().take(3).flatten().copied());
+        }
+
+        let (width, height) = size.unwrap_or_default();
+        let focals = original_sizes.iter().map(|(width, _)| {
+            (0.5 * *width as f64 / (0.5 * transforms.camera_angle_x).tan())
+                as f32
+        });
+
+        let images = Tensor::<B, 4>::from_data(
+            Data::new(
+                images,
+                Shape::new([image_count, height as usize, width as usize, 4]),
+            )
+            .convert(),
+            device,
+        );
+
+        let poses = Tensor::<B, 3>::from_data(
+            Data::new(poses, Shape::new([image_count, 3, 4])).convert(),
+            device,
+        );
+
+        let intrinsics = resized_intrinsics(
+            focals,
+            &original_sizes,
+            (width as usize, height as usize),
+        );
+
+        self.assemble(intrinsics, images, poses, None, None, device)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::*;
+    use super::*;
+    use image::RgbaImage;
+    use std::fs;
+
+    type Backend = burn::backend::Wgpu;
+
+    #[test]
+    fn simple_nerf_dataset_blender_loading() {
+        let device = Default::default();
+
+        let directory = temp_directory("blender-loading");
+        fs::create_dir_all(directory.join("train")).unwrap();
+        for index in 0..2 {
+            RgbaImage::from_raw(2, 2, [255, 0, 0, 255, 0, 0, 0, 0].repeat(2))
+                .unwrap()
+                .save(directory.join(format!("train/r_{}.png", index)))
+                .unwrap();
+        }
+        fs::write(
+            directory.join("transforms_train.json"),
+            r#"{
+                "camera_angle_x": 0.6911112070083618,
+                "frames": [
+                    {
+                        "file_path": "./train/r_0",
+                        "transform_matrix": [
+                            [1.0, 0.0, 0.0, 0.0],
+                            [0.0, 1.0, 0.0, 0.0],
+                            [0.0, 0.0, 1.0, 4.0],
+                            [0.0, 0.0, 0.0, 1.0]
+                        ]
+                    },
+                    {
+                        "file_path": "./train/r_1",
+                        "transform_matrix": [
+                            [1.0, 0.0, 0.0, 0.0],
+                            [0.0, 1.0, 0.0, 0.0],
+                            [0.0, 0.0, 1.0, 5.0],
+                            [0.0, 0.0, 0.0, 1.0]
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_blender_dir::<Backend>(&directory, "train", &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!(dataset.len(), 2);
+
+        let image = &dataset.inners[0].image;
+        assert_eq!(image.shape.dims, [2, 2, 3]);
+        assert_eq!(image.value[0..6], [1.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_background(Some([0.0, 0.0, 0.0]))
+            .init_from_blender_dir::<Backend>(&directory, "train", &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        let image = &dataset.inners[0].image;
+        assert_eq!(image.value[0..6], [1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_blender_dir::<Backend>(&directory, "test", &device);
+        assert!(dataset.is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use super::*;
+
+// NOTE: The fields without setters keep their defaults,
+// and the whole config is validated at once when it is built
+#[derive(Clone, Debug)]
+pub struct SimpleNerfDatasetConfigBuilder {
+    config: SimpleNerfDatasetConfig,
+    distance_range: Option<Range<f64>>,
+    points_per_ray: Option<usize>,
+}
+
+impl SimpleNerfDatasetConfig {
+    pub fn builder() -> SimpleNerfDatasetConfigBuilder {
+        SimpleNerfDatasetConfigBuilder {
+            config: SimpleNerfDatasetConfig::new(0, 0.0..0.0),
+            distance_range: None,
+            points_per_ray: None,
+        }
+    }
+
+    // NOTE: The checks which need the images, e.g. the crop region,
+    // are done while loading
+    pub fn validate(&self) -> Result<(), DatasetError> {
+        if self.points_per_ray == 0 {
+            return Err(DatasetError::InvalidConfig(
+                "Points per ray must be positive".into(),
+            ));
+        }
+        let range = check_distance_range(&self.distance_range)?;
+        if (self.ndc || self.sampling != DistanceSampling::Linear)
+            && range.start <= 0.0
+        {
+            return Err(DatasetError::InvalidConfig(format!(
+                "Distance range must start above zero: {:?}",
+                range
+            )));
+        }
+        if self.downscale == 0 {
+            return Err(DatasetError::InvalidConfig(
+                "Downscale factor must be positive".into(),
+            ));
+        }
+        if let Some((width, height)) = self.resize_to {
+            if width == 0
+                || height == 0
+                || width % self.downscale != 0
+                || height % self.downscale != 0
+            {
+                return Err(DatasetError::InvalidConfig(format!(
+                    "Downscale factor {} does not divide the images of {}x{}",
+                    self.downscale, width, height
+                )));
+            }
+        }
+        if let Some(ImageCrop::Center(fraction)) = self.crop {
+            if !(fraction > 0.0 && fraction <= 1.0) {
+                return Err(DatasetError::InvalidConfig(format!(
+                    "Crop fraction {} is not in (0, 1]",
+                    fraction
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SimpleNerfDatasetConfigBuilder {
+    pub fn points_per_ray(
+        mut self,
+        points_per_ray: usize,
+    ) -> Self {
+        self.points_per_ray = Some(points_per_ray);
+        self
+    }
+
+    pub fn distance_range(
+        mut self,
+        distance_range: Range<f64>,
+    ) -> Self {
+        self.distance_range = Some(distance_range);
+        self
+    }
+
+    pub fn background(
+        mut self,
+        background: Option<[f32; 3]>,
+    ) -> Self {
+        self.config.background = background;
+        self
+    }
+
+    pub fn crop(
+        mut self,
+        crop: ImageCrop,
+    ) -> Self {
+        self.config.crop = Some(crop);
+        self
+    }
+
+    pub fn downscale(
+        mut self,
+        downscale: usize,
+    ) -> Self {
+        self.config.downscale = downscale;
+        self
+    }
+
+    pub fn resize_to(
+        mut self,
+        width: usize,
+        height: usize,
+    ) -> Self {
+        self.config.resize_to = Some((width, height));
+        self
+    }
+
+    pub fn sampling(
+        mut self,
+        sampling: DistanceSampling,
+    ) -> Self {
+        self.config.sampling = sampling;
+        self
+    }
+
+    pub fn seed(
+        mut self,
+        seed: u64,
+    ) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> Result<SimpleNerfDatasetConfig, DatasetError> {
+        let points_per_ray = self.points_per_ray.ok_or_else(|| {
+            DatasetError::InvalidConfig("Points per ray is not set".into())
+        })?;
+        let distance_range = self.distance_range.ok_or_else(|| {
+            DatasetError::InvalidConfig("Distance range is not set".into())
+        })?;
+
+        let config = SimpleNerfDatasetConfig {
+            points_per_ray,
+            distance_range,
+            ..self.config
+        };
+        config.validate()?;
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::*;
+    use super::*;
+
+    type Backend = burn::backend::Wgpu;
+
+    #[test]
+    fn simple_nerf_dataset_config_builder() {
+        let device = Default::default();
+
+        let config = SimpleNerfDatasetConfig::builder()
+            .points_per_ray(8)
+            .distance_range(2.0..6.0)
+            .background(None)
+            .downscale(2)
+            .sampling(DistanceSampling::Disparity)
+            .seed(7)
+            .build();
+        assert!(config.is_ok(), "Error: {}", config.unwrap_err());
+
+        let config = config.unwrap();
+        assert_eq!(config.points_per_ray, 8);
+        assert_eq!(config.distance_range, 2.0..6.0);
+        assert_eq!(config.background, None);
+        assert_eq!(config.downscale, 2);
+        assert_eq!(config.seed, Some(7));
+        assert_eq!(config.image_layout, ImageLayout::Detect);
+        let dataset =
+            config.init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+        assert_eq!(dataset.unwrap().image_size(), (50, 50));
+
+        let invalid_builders = [
+            SimpleNerfDatasetConfig::builder().distance_range(2.0..6.0),
+            SimpleNerfDatasetConfig::builder().points_per_ray(8),
+            SimpleNerfDatasetConfig::builder()
+                .points_per_ray(0)
+                .distance_range(2.0..6.0),
+            SimpleNerfDatasetConfig::builder()
+                .points_per_ray(8)
+                .distance_range(6.0..2.0),
+            SimpleNerfDatasetConfig::builder()
+                .points_per_ray(8)
+                .distance_range(-1.0..2.0),
+            SimpleNerfDatasetConfig::builder()
+                .points_per_ray(8)
+                .distance_range(0.0..2.0)
+                .sampling(DistanceSampling::Log),
+            SimpleNerfDatasetConfig::builder()
+                .points_per_ray(8)
+                .distance_range(2.0..6.0)
+                .downscale(0),
+            SimpleNerfDatasetConfig::builder()
+                .points_per_ray(8)
+                .distance_range(2.0..6.0)
+                .resize_to(50, 50)
+                .downscale(4),
+            SimpleNerfDatasetConfig::builder()
+                .points_per_ray(8)
+                .distance_range(2.0..6.0)
+                .crop(ImageCrop::Center(1.5)),
+        ];
+        for builder in invalid_builders {
+            let result = builder.clone().build();
+            assert!(
+                matches!(result, Err(DatasetError::InvalidConfig(_))),
+                "Builder: {:?}",
+                builder
+            );
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use super::*;
+
+use burn::tensor::DataSerialize;
+use serde::{Deserialize, Serialize};
+use std::io::{BufReader, BufWriter, Write};
+
+const CACHE_MAGIC: &[u8; 8] = b"SNRFDSET";
+const CACHE_VERSION: u32 = 2;
+
+// NOTE: The config is embedded as JSON, so that a cache built with
+// another config is rejected instead of silently reused
+#[derive(Deserialize, Serialize)]
+struct DatasetCache {
+    channel_count: usize,
+    config: String,
+    debug_sampling: bool,
+    distance_noise: DistanceNoise,
+    focal: f32,
+    height: usize,
+    inners: Vec<InnerCache>,
+    seed: Option<u64>,
+    warnings: Vec<(usize, String)>,
+    width: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+struct InnerCache {
+    depth: Option<DataSerialize<f32>>,
+    directions: DataSerialize<f32>,
+    distance_scales: Option<Vec<f32>>,
+    distance_widths: Vec<f64>,
+    distances: Vec<f32>,
+    image: DataSerialize<f32>,
+    intrinsics: [f32; 4],
+    mask: Option<DataSerialize<f32>>,
+    origins: DataSerialize<f32>,
+    pose: Vec<f32>,
+}
+
+impl<B: Backend> SimpleNerfDataset<B> {
+    // NOTE: The cache has a magic and a format version in the header,
+    // followed by the inners encoded in MessagePack
+    pub fn save_cache(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(), DatasetError> {
+        check_image_transform(&self.config)?;
+
+        let cache = DatasetCache {
+            channel_count: self.channel_count,
+            config: serde_json::to_string(&self.config)?,
+            debug_sampling: self.debug_sampling,
+            distance_noise: self.distance_noise.clone(),
+            focal: self.focal,
+            height: self.height,
+            inners: self
+                .inners
+                .iter()
+                .map(|inner| InnerCache {
+                    depth: inner.depth.as_ref().map(Data::serialize),
+                    directions: inner.directions.serialize(),
+                    distance_scales: inner.distance_scales.clone(),
+                    distance_widths: inner.distance_widths.clone(),
+                    distances: inner.distances.clone(),
+                    image: inner.image.serialize(),
+                    intrinsics: inner.intrinsics,
+                    mask: inner.mask.as_ref().map(Data::serialize),
+                    origins: inner.origins.serialize(),
+                    pose: inner.pose.clone(),
+                })
+                .collect(),
+            seed: self.seed,
+            warnings: self
+                .warnings
+                .iter()
+                .map(|warning| (warning.index, warning.message.clone()))
+                .collect(),
+            width: self.width,
+        };
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(CACHE_MAGIC)?;
+        writer.write_all(&CACHE_VERSION.to_le_bytes())?;
+        rmp_serde::encode::write(&mut writer, &cache)
+            .map_err(|error| DatasetError::Parse(error.to_string()))?;
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+impl SimpleNerfDatasetConfig {
+    pub fn init_from_cache<B: Backend>(
+        &self,
+        path: impl AsRef<Path>,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        check_image_transform(self)?;
+
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != *CACHE_MAGIC {
+            return Err(DatasetError::Parse(
+                "Dataset cache has an unknown header".into(),
+            ));
+        }
+        let mut version = [0; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != CACHE_VERSION {
+            return Err(DatasetError::Parse(format!(
+                "Dataset cache version {} is not supported",
+                version
+            )));
+        }
+
+        let cache: DatasetCache = rmp_serde::from_read(reader)
+            .map_err(|error| DatasetError::Parse(error.to_string()))?;
+        // NOTE: Only the fields which change how the inners are built
+        // must agree, so e.g. the download or the parallel loading may differ
+        let config = serde_json::from_str::<serde_json::Value>(&cache.config)?;
+        let expected_config = serde_json::to_value(self)?;
+        if let Some(field) = [
+            "array_names",
+            "background",
+            "convention",
+            "crop",
+            "distance_range",
+            "distortion",
+            "downscale",
+            "image_indices",
+            "image_layout",
+            "invert_poses",
+            "ndc",
+            "normalize_directions",
+            "orthonormalize_poses",
+            "parse_mode",
+            "points_per_ray",
+            "resize_to",
+            "sampling",
+        ]
+        .iter()
+        .find(|field| config[**field] != expected_config[**field])
+        {
+            return Err(DatasetError::InvalidConfig(format!(
+                "Dataset cache is built with another {:?}: {}",
+                field, config[*field]
+            )));
+        }
+
+        Ok(SimpleNerfDataset {
+            channel_count: cache.channel_count,
+            config: self.clone(),
+            debug_sampling: cache.debug_sampling,
+            device: device.clone(),
+            distance_noise: cache.distance_noise,
+            epoch: 0,
+            focal: cache.focal,
+            height: cache.height,
+            inners: cache
+                .inners
+                .into_iter()
+                .map(|inner| SimpleNerfDatasetInner {
+                    depth: inner.depth.map(Data::from),
+                    directions: inner.directions.into(),
+                    distance_scales: inner.distance_scales,
+                    distance_widths: inner.distance_widths,
+                    distances: inner.distances,
+                    image: inner.image.into(),
+                    intrinsics: inner.intrinsics,
+                    mask: inner.mask.map(Data::from),
+                    origins: inner.origins.into(),
+                    pose: inner.pose,
+                })
+                .collect(),
+            items: None,
+            seed: cache.seed,
+            warnings: cache
+                .warnings
+                .into_iter()
+                .map(|(index, message)| ParseWarning {
+                    index,
+                    message,
+                })
+                .collect(),
+            width: cache.width,
+        })
+    }
+}
+
+// NOTE: The image transform is not serialized with the config,
+// so a cache of transformed images could not be told apart
+fn check_image_transform(
+    config: &SimpleNerfDatasetConfig
+) -> Result<(), DatasetError> {
+    match config.map_images.0 {
+        Some(_) => Err(DatasetError::InvalidConfig(
+            "Dataset cache cannot record an image transform".into(),
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::*;
+    use super::*;
+
+    type Backend = burn::backend::Wgpu;
+
+    #[test]
+    fn simple_nerf_dataset_cache_round_trip() {
+        let device = Default::default();
+        let config = SimpleNerfDatasetConfig::new(7, 2.0..6.0);
+        let path = temp_directory("dataset-cache").join("cache.bin");
+
+        let dataset = config
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let result = dataset.save_cache(&path);
+        assert!(result.is_ok(), "Error: {}", result.unwrap_err());
+
+        let cached = config.init_from_cache::<Backend>(&path, &device);
+        assert!(cached.is_ok(), "Error: {}", cached.unwrap_err());
+
+        let cached = cached.unwrap();
+        assert_eq!(cached.len(), dataset.len());
+        assert_eq!(cached.inners, dataset.inners);
+        assert_eq!(cached.image_size(), dataset.image_size());
+        assert_eq!(cached.focal(), dataset.focal());
+        for index in [0, dataset.len() - 1] {
+            let item = dataset.get(index).unwrap();
+            let cached_item = cached.get(index).unwrap();
+            assert_eq!(cached_item.directions, item.directions);
+            assert_eq!(cached_item.distances, item.distances);
+            assert_eq!(cached_item.image, item.image);
+            assert_eq!(cached_item.intervals, item.intervals);
+            assert_eq!(cached_item.positions, item.positions);
+        }
+
+        let result = SimpleNerfDatasetConfig::new(8, 2.0..6.0)
+            .init_from_cache::<Backend>(&path, &device);
+        assert!(matches!(result.unwrap_err(), DatasetError::InvalidConfig(_)));
+
+        let result = config
+            .clone()
+            .with_debug_sampling(true)
+            .with_download(DownloadConfig::new().with_retry_count(0))
+            .with_parallel_load(true)
+            .init_from_cache::<Backend>(&path, &device);
+        assert!(result.is_ok(), "Error: {}", result.unwrap_err());
+
+        let transformed = config
+            .clone()
+            .with_image_transform(|image: Tensor<Backend, 3>| image / 2.0);
+        let result = transformed.init_from_cache::<Backend>(&path, &device);
+        assert!(matches!(result.unwrap_err(), DatasetError::InvalidConfig(_)));
+
+        let result = transformed
+            .init_from_tensors::<Backend>(
+                Tensor::zeros([1, 2, 2, 3], &device),
+                Tensor::<Backend, 1>::from_floats(
+                    identity_poses(1).as_slice(),
+                    &device,
+                )
+                .reshape([1, 3, 4]),
+                2.0,
+                &device,
+            )
+            .unwrap()
+            .save_cache(&path);
+        assert!(matches!(result.unwrap_err(), DatasetError::InvalidConfig(_)));
+
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[8] = 0xff;
+        fs::write(&path, bytes).unwrap();
+        let result = config.init_from_cache::<Backend>(&path, &device);
+        assert!(matches!(result.unwrap_err(), DatasetError::Parse(_)));
+    }
+}
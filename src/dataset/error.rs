@@ -0,0 +1,181 @@
+use std::{error, fmt, io};
+use zip::result::ZipError;
+
+#[derive(Debug)]
+pub enum DatasetError {
+    ChecksumMismatch {
+        expected: String,
+        got: String,
+    },
+    InvalidConfig(String),
+    Download {
+        attempt_count: usize,
+        status: Option<reqwest::StatusCode>,
+        source: reqwest::Error,
+    },
+    InvalidPose {
+        index: usize,
+        message: String,
+    },
+    Io(io::Error),
+    MissingArray {
+        name: String,
+        available: Vec<String>,
+    },
+    Network(reqwest::Error),
+    Parse(String),
+    ShapeMismatch {
+        name: String,
+        expected: String,
+        got: Vec<usize>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseWarning {
+    pub index: usize,
+    pub message: String,
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            DatasetError::ChecksumMismatch {
+                expected,
+                got,
+            } => write!(
+                f,
+                "Downloaded SHA-256 checksum is {} instead of {}",
+                got, expected
+            ),
+            DatasetError::InvalidConfig(message) => {
+                write!(f, "Invalid dataset config: {}", message)
+            },
+            DatasetError::Download {
+                attempt_count,
+                status,
+                source,
+            } => match status {
+                Some(status) => write!(
+                    f,
+                    "Download failed after {} attempts with status {}: {}",
+                    attempt_count, status, source
+                ),
+                None => write!(
+                    f,
+                    "Download failed after {} attempts: {}",
+                    attempt_count, source
+                ),
+            },
+            DatasetError::InvalidPose {
+                index,
+                message,
+            } => write!(f, "Pose of image {} is invalid: {}", index, message),
+            DatasetError::Io(error) => write!(f, "I/O error: {}", error),
+            DatasetError::MissingArray {
+                name,
+                available,
+            } => {
+                if available.is_empty() {
+                    write!(f, "Array {:?} is missing", name)
+                } else {
+                    write!(
+                        f,
+                        "Array {:?} is missing, and the available arrays are {:?}",
+                        name, available
+                    )
+                }
+            },
+            DatasetError::Network(error) => {
+                write!(f, "Network error: {}", error)
+            },
+            DatasetError::Parse(message) => {
+                write!(f, "Parse error: {}", message)
+            },
+            DatasetError::ShapeMismatch {
+                name,
+                expected,
+                got,
+            } => write!(
+                f,
+                "Array {:?} has the shape {:?} instead of {}",
+                name, got, expected
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "Image {} is skipped: {}", self.index, self.message)
+    }
+}
+
+impl error::Error for DatasetError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DatasetError::Download {
+                source,
+                ..
+            } => Some(source),
+            DatasetError::Io(error) => Some(error),
+            DatasetError::Network(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DatasetError {
+    fn from(error: io::Error) -> Self {
+        DatasetError::Io(error)
+    }
+}
+
+impl From<npyz::DTypeError> for DatasetError {
+    fn from(error: npyz::DTypeError) -> Self {
+        DatasetError::Parse(error.to_string())
+    }
+}
+
+impl From<reqwest::Error> for DatasetError {
+    fn from(error: reqwest::Error) -> Self {
+        DatasetError::Network(error)
+    }
+}
+
+impl From<serde_json::Error> for DatasetError {
+    fn from(error: serde_json::Error) -> Self {
+        DatasetError::Parse(error.to_string())
+    }
+}
+
+impl From<ZipError> for DatasetError {
+    fn from(error: ZipError) -> Self {
+        match error {
+            ZipError::Io(error) => DatasetError::Io(error),
+            error => DatasetError::Parse(error.to_string()),
+        }
+    }
+}
+
+impl From<DatasetError> for io::Error {
+    fn from(error: DatasetError) -> Self {
+        match error {
+            DatasetError::Io(error) => error,
+            DatasetError::InvalidConfig(_) => {
+                io::Error::new(io::ErrorKind::InvalidInput, error)
+            },
+            DatasetError::Download {
+                ..
+            }
+            | DatasetError::Network(_) => io::Error::other(error),
+            _ => io::Error::new(io::ErrorKind::InvalidData, error),
+        }
+    }
+}
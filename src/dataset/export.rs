@@ -0,0 +1,182 @@
+use super::*;
+
+use npyz::{AutoSerialize, WriteOptions, WriterBuilder};
+use std::io::Write;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+impl<B: Backend> SimpleNerfDataset<B> {
+    // NOTE: The archive has "images", "poses", "focal" or "intrinsics" and
+    // optionally "depths", and the masks are written back as the alpha channel
+    // unless the colors are already composited onto the background
+    pub fn write_npz(
+        &self,
+        writer: impl Write + Seek,
+    ) -> io::Result<()> {
+        let image_count = self.inners.len() as u64;
+        let height = self.height as u64;
+        let width = self.width as u64;
+        let has_mask = self.config.background.is_none()
+            && self.inners.iter().all(|inner| inner.mask.is_some())
+            && !self.inners.is_empty();
+
+        let mut archive = ZipWriter::new(writer);
+
+        let images = if has_mask {
+            self.inners
+                .iter()
+                .flat_map(|inner| {
+                    let alphas = inner
+                        .mask
+                        .as_ref()
+                        .map_or(&[][..], |mask| mask.value.as_slice());
+                    inner.image.value.chunks_exact(3).zip(alphas).flat_map(
+                        |(color, alpha)| [color[0], color[1], color[2], *alpha],
+                    )
+                })
+                .collect::<Vec<_>>()
+        } else {
+            self.inners
+                .iter()
+                .flat_map(|inner| inner.image.value.iter().copied())
+                .collect()
+        };
+        let channel_count = if has_mask {
+            4
+        } else {
+            self.channel_count as u64
+        };
+        write_npz_array(
+            &mut archive,
+            "images",
+            &[image_count, height, width, channel_count],
+            images,
+        )?;
+
+        write_npz_array(
+            &mut archive,
+            "poses",
+            &[image_count, 3, 4],
+            self.inners
+                .iter()
+                .flat_map(|inner| inner.pose.iter().copied())
+                .collect(),
+        )?;
+
+        if self.inners.iter().all(|inner| inner.depth.is_some())
+            && !self.inners.is_empty()
+        {
+            write_npz_array(
+                &mut archive,
+                "depths",
+                &[image_count, height, width],
+                self.inners
+                    .iter()
+                    .flat_map(|inner| {
+                        inner.depth.iter().flat_map(|depth| depth.value.clone())
+                    })
+                    .collect(),
+            )?;
+        }
+
+        // NOTE: The focal lengths alone are enough for centered square pixels
+        let is_centered = self.inners.iter().all(|inner| {
+            let [focal_x, focal_y, center_x, center_y] = inner.intrinsics;
+            focal_x == focal_y
+                && center_x == self.width as f32 / 2.0
+                && center_y == self.height as f32 / 2.0
+        });
+        if is_centered {
+            write_npz_array(
+                &mut archive,
+                "focal",
+                &[image_count],
+                self.inners.iter().map(|inner| inner.intrinsics[0]).collect(),
+            )?;
+        } else {
+            write_npz_array(
+                &mut archive,
+                "intrinsics",
+                &[image_count, 3, 3],
+                self.inners
+                    .iter()
+                    .flat_map(|inner| {
+                        let [focal_x, focal_y, center_x, center_y] =
+                            inner.intrinsics;
+                        [
+                            focal_x, 0.0, center_x, 0.0, focal_y, center_y,
+                            0.0, 0.0, 1.0,
+                        ]
+                    })
+                    .collect(),
+            )?;
+        }
+
+        archive.finish().map_err(DatasetError::from)?;
+
+        Ok(())
+    }
+}
+
+fn write_npz_array<W: Write + Seek, T: AutoSerialize>(
+    archive: &mut ZipWriter<W>,
+    name: &str,
+    shape: &[u64],
+    values: Vec<T>,
+) -> io::Result<()> {
+    archive
+        .start_file(
+            npz::file_name_from_array_name(name),
+            SimpleFileOptions::default(),
+        )
+        .map_err(DatasetError::from)?;
+
+    let mut writer = WriteOptions::new()
+        .default_dtype()
+        .shape(shape)
+        .writer(archive)
+        .begin_nd()?;
+    writer.extend(values)?;
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::*;
+    use super::*;
+
+    type Backend = burn::backend::Wgpu;
+
+    #[test]
+    fn simple_nerf_dataset_npz_export() {
+        let device = Default::default();
+
+        let config = SimpleNerfDatasetConfig::new(4, 2.0..6.0);
+        let dataset = config
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+
+        let mut bytes = io::Cursor::new(vec![]);
+        let result = dataset.write_npz(&mut bytes);
+        assert!(result.is_ok(), "Error: {}", result.unwrap_err());
+
+        let exported =
+            config.init_from_bytes::<Backend>(bytes.into_inner(), &device);
+        assert!(exported.is_ok(), "Error: {}", exported.unwrap_err());
+
+        let exported = exported.unwrap();
+        assert_eq!(exported.len(), dataset.len());
+        assert_eq!(exported.focal(), dataset.focal());
+        assert_eq!(exported.height(), dataset.height());
+        assert_eq!(exported.width(), dataset.width());
+
+        for index in [0, dataset.len() - 1] {
+            let inner = &dataset.inners[index];
+            let exported_inner = &exported.inners[index];
+            assert_eq!(exported_inner.image, inner.image);
+            assert_eq!(exported_inner.pose, inner.pose);
+            assert_eq!(exported_inner.intrinsics, inner.intrinsics);
+            exported_inner.directions.assert_approx_eq(&inner.directions, 5);
+            exported_inner.origins.assert_approx_eq(&inner.origins, 5);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use super::*;
+
+impl SimpleNerfDatasetConfig {
+    // NOTE: The poses file is either a JSON array or a CSV file,
+    // which has a 4x4 camera-to-world matrix for each image
+    pub fn init_from_image_dir<B: Backend>(
+        &self,
+        directory: impl AsRef<Path>,
+        poses_file_path: impl AsRef<Path>,
+        focal: f32,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        let poses_file_path = poses_file_path.as_ref();
+
+        let matrices = {
+            let text = fs::read_to_string(poses_file_path)?;
+            let is_json = poses_file_path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    extension.eq_ignore_ascii_case("json")
+                });
+            if is_json {
+                read_json_matrices(&text)?
+            } else {
+                read_csv_matrices(&text)?
+            }
+        };
+
+        let image_paths = list_image_files(directory.as_ref())?;
+        let image_count = image_paths.len();
+        if image_count != matrices.len() {
+            return Err(DatasetError::ShapeMismatch {
+                name: "poses".into(),
+                expected: format!("[{}, 4, 4]", image_count),
+                got: vec![matrices.len(), 4, 4],
+            });
+        }
+
+        let (images, (width, height), original_sizes) =
+            read_rgb_images(&image_paths, self.resize_to)?;
+
+        let images = Tensor::<B, 4>::from_data(
+            Data::new(
+                images,
+                Shape::new([image_count, height as usize, width as usize, 3]),
+            )
+            .convert(),
+            device,
+        );
+
+        let poses = Tensor::<B, 3>::from_data(
+            Data::new(
+                matrices
+                    .iter()
+                    .flat_map(|matrix| matrix[0..12].iter().copied())
+                    .collect(),
+                Shape::new([image_count, 3, 4]),
+            )
+            .convert(),
+            device,
+        );
+
+        let intrinsics = resized_intrinsics(
+            vec![focal; image_count],
+            &original_sizes,
+            (width as usize, height as usize),
+        );
+
+        self.assemble(intrinsics, images, poses, None, None, device)
+    }
+}
+
+// NOTE: Each matrix may be nested or flattened
+fn read_json_matrices(text: &str) -> Result<Vec<Vec<f32>>, DatasetError> {
+    fn flatten(
+        value: &serde_json::Value,
+        values: &mut Vec<f32>,
+    ) -> Result<(), DatasetError> {
+        match value {
+            serde_json::Value::Array(items) => {
+                items.iter().try_for_each(|item| flatten(item, values))
+            },
+            serde_json::Value::Number(number) => {
+                values.push(number.as_f64().unwrap_or(f64::NAN) as f32);
+                Ok(())
+            },
+            _ => Err(DatasetError::Parse(format!(
+                "Pose value is not a number: {}",
+                value
+            ))),
+        }
+    }
+
+    let matrices: Vec<serde_json::Value> = serde_json::from_str(text)?;
+    matrices
+        .iter()
+        .map(|matrix| {
+            let mut values = vec![];
+            flatten(matrix, &mut values)?;
+            check_matrix(values)
+        })
+        .collect()
+}
+
+fn read_csv_matrices(text: &str) -> Result<Vec<Vec<f32>>, DatasetError> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let values = line
+                .split(',')
+                .map(|value| {
+                    value.trim().parse::<f32>().map_err(|error| {
+                        DatasetError::Parse(format!(
+                            "Pose value {:?} is invalid: {}",
+                            value, error
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            check_matrix(values)
+        })
+        .collect()
+}
+
+fn check_matrix(values: Vec<f32>) -> Result<Vec<f32>, DatasetError> {
+    if values.len() != 16 {
+        return Err(DatasetError::ShapeMismatch {
+            name: "poses".into(),
+            expected: "[4, 4]".into(),
+            got: vec![values.len()],
+        });
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::*;
+    use super::*;
+    use image::RgbImage;
+
+    type Backend = burn::backend::Wgpu;
+
+    #[test]
+    fn simple_nerf_dataset_image_dir_loading() {
+        let device = Default::default();
+
+        let directory = temp_directory("image-dir-loading");
+        let image_directory = directory.join("images");
+        fs::create_dir_all(&image_directory).unwrap();
+        for index in 0..2 {
+            RgbImage::from_pixel(4, 2, image::Rgb([255, 0, 0]))
+                .save(image_directory.join(format!("{:03}.png", index)))
+                .unwrap();
+        }
+        fs::write(
+            directory.join("poses.json"),
+            r#"[
+                [[1, 0, 0, 0], [0, 1, 0, 0], [0, 0, 1, 4], [0, 0, 0, 1]],
+                [1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 5, 0, 0, 0, 1]
+            ]"#,
+        )
+        .unwrap();
+        fs::write(
+            directory.join("poses.csv"),
+            "1,0,0,0,0,1,0,0,0,0,1,4,0,0,0,1\n\
+             1,0,0,0,0,1,0,0,0,0,1,5,0,0,0,1\n",
+        )
+        .unwrap();
+
+        let datasets = ["poses.json", "poses.csv"].map(|poses_file_name| {
+            SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+                .init_from_image_dir::<Backend>(
+                    &image_directory,
+                    directory.join(poses_file_name),
+                    2.0,
+                    &device,
+                )
+        });
+        for dataset in datasets {
+            assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+            let dataset = dataset.unwrap();
+            assert_eq!(dataset.len(), 2);
+            assert_eq!(dataset.inners[1].image.shape.dims, [2, 4, 3]);
+            assert_eq!(dataset.inners[1].origins.value[0..3], [0.0, 0.0, 5.0]);
+        }
+
+        fs::write(
+            directory.join("poses.csv"),
+            "1,0,0,0,0,1,0,0,0,0,1,4,0,0,0,1\n",
+        )
+        .unwrap();
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_image_dir::<Backend>(
+                &image_directory,
+                directory.join("poses.csv"),
+                2.0,
+                &device,
+            );
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::ShapeMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_image_dir_resizing() {
+        let device = Default::default();
+
+        let directory = temp_directory("image-dir-resizing");
+        let image_directory = directory.join("images");
+        fs::create_dir_all(&image_directory).unwrap();
+        RgbImage::from_pixel(4, 2, image::Rgb([255, 0, 0]))
+            .save(image_directory.join("000.png"))
+            .unwrap();
+        RgbImage::from_pixel(8, 4, image::Rgb([255, 0, 0]))
+            .save(image_directory.join("001.png"))
+            .unwrap();
+        fs::write(
+            directory.join("poses.csv"),
+            "1,0,0,0,0,1,0,0,0,0,1,4,0,0,0,1\n\
+             1,0,0,0,0,1,0,0,0,0,1,5,0,0,0,1\n",
+        )
+        .unwrap();
+
+        let config = SimpleNerfDatasetConfig::new(4, 2.0..6.0);
+        let dataset = config.init_from_image_dir::<Backend>(
+            &image_directory,
+            directory.join("poses.csv"),
+            2.0,
+            &device,
+        );
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::ShapeMismatch { .. }
+        ));
+
+        let dataset =
+            config.with_resize_to(Some((4, 2))).init_from_image_dir::<Backend>(
+                &image_directory,
+                directory.join("poses.csv"),
+                2.0,
+                &device,
+            );
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!((dataset.width(), dataset.height()), (4, 2));
+        assert_eq!(dataset.inners[0].intrinsics, [2.0, 2.0, 2.0, 1.0]);
+        assert_eq!(dataset.inners[1].intrinsics, [1.0, 1.0, 2.0, 1.0]);
+    }
+}
@@ -0,0 +1,145 @@
+use super::*;
+
+impl SimpleNerfDatasetConfig {
+    pub fn init_from_llff_dir<B: Backend>(
+        &self,
+        directory: impl AsRef<Path>,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        let directory = directory.as_ref();
+
+        let poses_bounds = NpyFile::new(io::BufReader::new(File::open(
+            directory.join("poses_bounds.npy"),
+        )?))?;
+        let shape = poses_bounds.shape().to_vec();
+        if shape.len() != 2 || shape[1] != 17 {
+            return Err(DatasetError::ShapeMismatch {
+                name: "poses_bounds".into(),
+                expected: "[N, 17]".into(),
+                got: shape.iter().map(|dim| *dim as usize).collect(),
+            });
+        }
+        let poses_bounds = poses_bounds.into_vec::<f64>()?;
+
+        let image_paths = list_image_files(&directory.join("images"))?;
+
+        let image_count = image_paths.len();
+        if image_count != shape[0] as usize {
+            return Err(DatasetError::ShapeMismatch {
+                name: "poses_bounds".into(),
+                expected: format!("[{}, 17]", image_count),
+                got: shape.iter().map(|dim| *dim as usize).collect(),
+            });
+        }
+
+        let (images, (width, height), original_sizes) =
+            read_rgb_images(&image_paths, self.resize_to)?;
+
+        // NOTE: The rotation of LLFF poses is in [down, right, back] order,
+        // which is converted to [right, up, back] order
+        let mut poses = vec![];
+        let mut bounds = vec![];
+        for row in poses_bounds.chunks_exact(17) {
+            for matrix_row in row[0..15].chunks_exact(5) {
+                poses.extend([
+                    matrix_row[1] as f32,
+                    -matrix_row[0] as f32,
+                    matrix_row[2] as f32,
+                    matrix_row[3] as f32,
+                ]);
+            }
+            bounds.push(row[15]..row[16]);
+        }
+
+        // NOTE: The images may be downsampled from the original resolution
+        let focals = {
+            let original_width = poses_bounds[9];
+            let original_focal = poses_bounds[14];
+            original_sizes
+                .iter()
+                .map(|(width, _)| {
+                    (original_focal * *width as f64 / original_width) as f32
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let images = Tensor::<B, 4>::from_data(
+            Data::new(
+                images,
+                Shape::new([image_count, height as usize, width as usize, 3]),
+            )
+            .convert(),
+            device,
+        );
+
+        let poses = Tensor::<B, 3>::from_data(
+            Data::new(poses, Shape::new([image_count, 3, 4])).convert(),
+            device,
+        );
+
+        let intrinsics = resized_intrinsics(
+            focals,
+            &original_sizes,
+            (width as usize, height as usize),
+        );
+
+        self.assemble(intrinsics, images, poses, Some(bounds), None, device)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::*;
+    use super::*;
+    use image::RgbImage;
+
+    type Backend = burn::backend::Wgpu;
+
+    #[test]
+    fn simple_nerf_dataset_llff_loading() {
+        let device = Default::default();
+
+        let directory = temp_directory("llff-loading");
+        fs::create_dir_all(directory.join("images")).unwrap();
+        for index in 0..2 {
+            RgbImage::new(4, 2)
+                .save(directory.join(format!("images/{:03}.png", index)))
+                .unwrap();
+        }
+        let poses_bounds = [
+            [0.0, 1.0, 0.0, 0.0, 4.0],
+            [-1.0, 0.0, 0.0, 0.0, 8.0],
+            [0.0, 0.0, 1.0, 4.0, 10.0],
+        ]
+        .concat()
+        .into_iter()
+        .chain([1.0, 5.0])
+        .chain(
+            [
+                [0.0, 1.0, 0.0, 0.0, 4.0],
+                [-1.0, 0.0, 0.0, 0.0, 8.0],
+                [0.0, 0.0, 1.0, 5.0, 10.0],
+            ]
+            .concat(),
+        )
+        .chain([2.0, 7.0])
+        .collect::<Vec<f64>>();
+        fs::write(
+            directory.join("poses_bounds.npy"),
+            npy_bytes(&[2, 17], poses_bounds),
+        )
+        .unwrap();
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_llff_dir::<Backend>(&directory, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!(dataset.len(), 2);
+
+        let inner = &dataset.inners[1];
+        assert_eq!(inner.image.shape.dims, [2, 4, 3]);
+        assert_eq!(inner.distance_widths, [1.25; 4]);
+        assert_eq!(inner.distances[0..4], [2.0, 3.25, 4.5, 5.75]);
+    }
+}
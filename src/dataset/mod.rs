@@ -0,0 +1,5138 @@
+mod batcher;
+mod blender;
+mod builder;
+mod cache;
+mod error;
+mod export;
+mod image_dir;
+mod llff;
+mod ray_batches;
+mod remote;
+mod render_poses;
+
+pub use self::{
+    batcher::*,
+    builder::SimpleNerfDatasetConfigBuilder,
+    error::*,
+    ray_batches::{SimpleNerfRayBatchDataset, SimpleNerfRayBatchItem},
+    remote::DownloadConfig,
+    render_poses::SimpleNerfCameraRays,
+};
+
+#[cfg(feature = "async")]
+pub use self::remote::DatasetFuture;
+
+use burn::{
+    data::dataset::Dataset,
+    prelude::*,
+    tensor::{
+        module::{avg_pool2d, interpolate},
+        ops::{InterpolateMode, InterpolateOptions},
+        Distribution,
+    },
+};
+use flate2::read::GzDecoder;
+use log::{debug, info, trace, warn};
+use npyz::{npz, DType, NpyFile, TypeChar};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    any::Any,
+    collections::HashMap,
+    convert::TryFrom,
+    fmt,
+    fs::{self, File},
+    io::{self, Read, Seek},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use zip::{read::ZipFile, result::ZipError, ZipArchive};
+
+#[derive(Config, Debug)]
+pub struct SimpleNerfDatasetConfig {
+    pub points_per_ray: usize,
+    pub distance_range: Range<f64>,
+    #[config(default = "ArrayNames::new()")]
+    pub array_names: ArrayNames,
+    #[config(default = "Some([1.0, 1.0, 1.0])")]
+    pub background: Option<[f32; 3]>,
+    #[config(default = "CameraConvention::OpenGl")]
+    pub convention: CameraConvention,
+    pub crop: Option<ImageCrop>,
+    // NOTE: The sampling intervals are only traced through the log crate,
+    // and nothing is printed to the standard output
+    #[config(default = false)]
+    pub debug_sampling: bool,
+    #[config(default = "DistanceNoise::Uniform { scale: 1.0 }")]
+    pub distance_noise: DistanceNoise,
+    pub distortion: Option<[f32; 4]>,
+    #[config(default = "DownloadConfig::new()")]
+    pub download: DownloadConfig,
+    #[config(default = 1)]
+    pub downscale: usize,
+    pub image_indices: Option<Vec<usize>>,
+    #[config(default = "ImageLayout::Detect")]
+    pub image_layout: ImageLayout,
+    #[config(default = false)]
+    pub invert_poses: bool,
+    #[config(default = "ImageTransform::default()")]
+    pub map_images: ImageTransform,
+    #[config(default = false)]
+    pub ndc: bool,
+    #[config(default = false)]
+    pub normalize_directions: bool,
+    #[config(default = false)]
+    pub orthonormalize_poses: bool,
+    #[config(default = false)]
+    pub parallel_load: bool,
+    #[config(default = "ParseMode::Strict")]
+    pub parse_mode: ParseMode,
+    pub resize_to: Option<(usize, usize)>,
+    #[config(default = "DistanceSampling::Linear")]
+    pub sampling: DistanceSampling,
+    #[config(default = "SamplingStrategy::Stratified")]
+    pub sampling_strategy: SamplingStrategy,
+    pub seed: Option<u64>,
+}
+
+// NOTE: The names of the arrays in an archive or a directory
+#[derive(Config, Debug, PartialEq)]
+pub struct ArrayNames {
+    #[config(default = "String::from(\"focal\")")]
+    pub focal: String,
+    #[config(default = "String::from(\"images\")")]
+    pub images: String,
+    #[config(default = "String::from(\"poses\")")]
+    pub poses: String,
+}
+
+// NOTE: OpenGL cameras look at -z with y up,
+// while OpenCV cameras look at +z with y down
+#[derive(Config, Debug, PartialEq)]
+pub enum CameraConvention {
+    OpenGl,
+    OpenCv,
+}
+
+// NOTE: The center crop keeps a fraction of each side,
+// and the region is [left, top, width, height] in pixels
+#[derive(Config, Debug, PartialEq)]
+pub enum ImageCrop {
+    Center(f32),
+    Region([usize; 4]),
+}
+
+// NOTE: The detected layout is channel-first only if the channels can not be
+// the last dimension, e.g. [N, 3, H, W] with W not in 1, 3 or 4
+#[derive(Config, Debug, PartialEq)]
+pub enum ImageLayout {
+    Detect,
+    ChannelsFirst,
+    ChannelsLast,
+}
+
+#[derive(Config, Debug, PartialEq)]
+pub enum DistanceSampling {
+    Linear,
+    Disparity,
+    Log,
+}
+
+// NOTE: The stratified strategy draws one uniform sample in each bin of
+// the training rays, while the deterministic one keeps the bin starts
+#[derive(Config, Debug, PartialEq)]
+pub enum SamplingStrategy {
+    Stratified,
+    Deterministic,
+}
+
+// NOTE: The noise is in units of the bin width and only applies to
+// the training split. The uniform noise starts at the bin start, while
+// the gaussian noise centers at the bin middle, and both stay in the bin
+#[derive(Config, Debug, PartialEq)]
+pub enum DistanceNoise {
+    Uniform {
+        scale: f32,
+    },
+    Gaussian {
+        std: f32,
+    },
+    None,
+}
+
+// NOTE: The strict mode fails at the first invalid image,
+// while the lenient mode skips it with a warning
+#[derive(Config, Debug, PartialEq)]
+pub enum ParseMode {
+    Strict,
+    Lenient,
+}
+
+// NOTE: The transform is not serialized with the config,
+// and it only applies to the backend it is created for
+#[derive(Clone, Default)]
+pub struct ImageTransform(Option<Arc<dyn Any + Send + Sync>>);
+
+type ImageTransformFn<B> =
+    Arc<dyn Fn(Tensor<B, 3>) -> Tensor<B, 3> + Send + Sync>;
+
+// NOTE: The rays and the image of an image before conversion
+struct RayData<E> {
+    directions: Data<E, 4>,
+    image: Data<E, 3>,
+    origins: Data<E, 4>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimpleNerfDataset<B: Backend> {
+    channel_count: usize,
+    config: SimpleNerfDatasetConfig,
+    debug_sampling: bool,
+    device: B::Device,
+    distance_noise: DistanceNoise,
+    epoch: usize,
+    focal: f32,
+    height: usize,
+    inners: Vec<SimpleNerfDatasetInner>,
+    items: Option<Vec<SimpleNerfData>>,
+    seed: Option<u64>,
+    warnings: Vec<ParseWarning>,
+    width: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct SimpleNerfDatasetInner {
+    directions: Data<f32, 4>,
+    // NOTE: The norms of the directions of all pixels before they are
+    // normalized, which scale the distances along the rays of each pixel
+    distance_scales: Option<Vec<f32>>,
+    distance_widths: Vec<f64>,
+    distances: Vec<f32>,
+    depth: Option<Data<f32, 3>>,
+    image: Data<f32, 3>,
+    intrinsics: [f32; 4],
+    mask: Option<Data<f32, 3>>,
+    origins: Data<f32, 4>,
+    pose: Vec<f32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimpleNerfData {
+    pub depth: Option<Data<f32, 3>>,
+    pub directions: Data<f32, 4>,
+    pub distances: Data<f32, 4>,
+    pub image: Data<f32, 3>,
+    pub intervals: Data<f32, 4>,
+    pub mask: Option<Data<f32, 3>>,
+    pub positions: Data<f32, 4>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimpleNerfInput<B: Backend> {
+    pub depth: Option<Tensor<B, 3>>,
+    pub directions: Tensor<B, 4>,
+    pub distances: Tensor<B, 4>,
+    pub image: Tensor<B, 3>,
+    pub intervals: Tensor<B, 4>,
+    pub mask: Option<Tensor<B, 3>>,
+    pub positions: Tensor<B, 4>,
+}
+
+// NOTE: Rays are flattened in row-major order of pixels,
+// so the ray at index `y * width + x` is cast through the pixel `(x, y)`
+#[derive(Clone, Debug)]
+pub struct RayBatch<B: Backend> {
+    pub depth: Option<Tensor<B, 2>>,
+    pub directions: Tensor<B, 3>,
+    pub distances: Tensor<B, 3>,
+    pub image: Tensor<B, 2>,
+    pub intervals: Tensor<B, 3>,
+    pub mask: Option<Tensor<B, 2>>,
+    pub positions: Tensor<B, 3>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimpleNerfDatasetSplit<B: Backend> {
+    pub test: SimpleNerfDataset<B>,
+    pub train: SimpleNerfDataset<B>,
+}
+
+// NOTE: Each item is constructed only when the iterator is advanced
+#[derive(Clone, Debug)]
+pub struct SimpleNerfDatasetIter<'a, B: Backend> {
+    dataset: &'a SimpleNerfDataset<B>,
+    index: usize,
+}
+
+impl SimpleNerfDatasetConfig {
+    // NOTE: The NDC distance `t` of a forward-facing ray is at the depth
+    // `near / (1 - t)` in front of the camera
+    pub fn ndc_to_depth<B: Backend, const D: usize>(
+        &self,
+        distances: Tensor<B, D>,
+    ) -> Tensor<B, D> {
+        (-distances + 1.0).recip() * self.distance_range.start as f32
+    }
+
+    // NOTE: The transform maps each image of [H, W, C] before it is stored,
+    // and it must keep the shape
+    pub fn with_image_transform<B: Backend, F>(
+        self,
+        transform: F,
+    ) -> Self
+    where
+        F: Fn(Tensor<B, 3>) -> Tensor<B, 3> + Send + Sync + 'static,
+    {
+        self.with_map_images(ImageTransform::new(transform))
+    }
+
+    // NOTE: The container is either a zip (npz) or a gzipped tarball,
+    // which is detected by the magic bytes
+    pub fn init_from_reader<B: Backend, R: io::Read + io::Seek>(
+        &self,
+        mut reader: R,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        let position = reader.stream_position()?;
+        let mut magic = [0; 2];
+        reader.read_exact(&mut magic)?;
+        reader.seek(io::SeekFrom::Start(position))?;
+        if magic == [0x1f, 0x8b] {
+            return self.init_from_tar_gz_reader(reader, device);
+        }
+
+        let mut archive = ZipArchive::new(reader)?;
+        // NOTE: Each array is buffered since the zip entry borrows the archive
+        self.read_arrays(
+            |name| {
+                let mut bytes = vec![];
+                find_npz_file(&mut archive, name)?.read_to_end(&mut bytes)?;
+                open_npy(io::Cursor::new(bytes), name)
+            },
+            device,
+        )
+    }
+
+    // NOTE: The images are of [N, H, W, 3] or [N, H, W, 4],
+    // and the poses are of [N, 3, 4] or [N, 4, 4]
+    pub fn init_from_tensors<B: Backend>(
+        &self,
+        images: Tensor<B, 4>,
+        poses: Tensor<B, 3>,
+        focal: f32,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        let intrinsics = centered_intrinsics(focal, &images);
+        self.assemble(intrinsics, images, poses, None, None, device)
+    }
+
+    fn init_from_tar_gz_reader<B: Backend, R: io::Read>(
+        &self,
+        reader: R,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        let mut arrays = HashMap::new();
+        for entry in tar::Archive::new(GzDecoder::new(reader)).entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.into_owned();
+            if path.extension().and_then(|extension| extension.to_str())
+                != Some("npy")
+            {
+                continue;
+            }
+            if let Some(name) = path.file_stem().and_then(|name| name.to_str())
+            {
+                let mut bytes = vec![];
+                entry.read_to_end(&mut bytes)?;
+                arrays.insert(name.to_owned(), bytes);
+            }
+        }
+        let read_tar_array = |name: &str| match arrays.get(name) {
+            Some(bytes) => open_npy(bytes.as_slice(), name),
+            None => Err(DatasetError::MissingArray {
+                name: name.into(),
+                available: {
+                    let mut names = arrays.keys().cloned().collect::<Vec<_>>();
+                    names.sort();
+                    names
+                },
+            }),
+        };
+
+        self.read_arrays(read_tar_array, device)
+    }
+
+    // NOTE: The arrays are looked up by name in any container,
+    // and the optional ones are skipped if they are missing
+    fn read_arrays<B: Backend, R: io::Read>(
+        &self,
+        mut lookup: impl FnMut(&str) -> Result<NpyFile<R>, DatasetError>,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        // NOTE: The images are read first since the focal can be derived
+        // from the camera angle and the image width
+        let images = self.image_layout.arrange(read_images(
+            lookup(&self.array_names.images)?,
+            device,
+            &self.parse_mode,
+        )?)?;
+        let images = match optional(lookup("masks"))? {
+            Some(array) => attach_masks(images, read_masks(array, device)?)?,
+            None => images,
+        };
+        let poses = read_poses(
+            lookup(&self.array_names.poses)?,
+            device,
+            &self.parse_mode,
+        )?;
+        let focals = optional(lookup(&self.array_names.focal))?
+            .map(read_focals)
+            .transpose()?;
+        let focals = match focals {
+            Some(focals) => {
+                if matches!(optional(lookup("camera_angle_x")), Ok(Some(_))) {
+                    warn_camera_angle_ignored(&self.array_names.focal);
+                }
+                Some(focals)
+            },
+            None => optional(lookup("camera_angle_x"))?
+                .map(|array| read_camera_angles(array, &images))
+                .transpose()?,
+        };
+        let intrinsics =
+            optional(lookup("intrinsics"))?.map(read_intrinsics).transpose()?;
+        let intrinsics = resolve_intrinsics(
+            focals,
+            intrinsics,
+            &images,
+            &self.array_names.focal,
+        )?;
+        let bounds = optional(lookup("bounds"))?
+            .map(|array| read_bounds(array, "bounds"))
+            .transpose()?;
+        let bounds = match bounds {
+            Some(bounds) => Some(bounds),
+            None => optional(lookup("near_far"))?
+                .map(|array| read_bounds(array, "near_far"))
+                .transpose()?,
+        };
+        let depths = optional(lookup("depths"))?
+            .map(|array| read_depths(array, device))
+            .transpose()?;
+
+        self.assemble(intrinsics, images, poses, bounds, depths, device)
+    }
+
+    fn assemble<B: Backend>(
+        &self,
+        intrinsics: Vec<[f32; 4]>,
+        images: Tensor<B, 4>,
+        poses: Tensor<B, 3>,
+        bounds: Option<Vec<Range<f64>>>,
+        depths: Option<Tensor<B, 3>>,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        let points_per_ray = self.points_per_ray;
+        if points_per_ray == 0 {
+            return Err(DatasetError::InvalidConfig(
+                "Points per ray must be positive".into(),
+            ));
+        }
+
+        let [image_count, _, _, channel_count] = images.dims();
+        let [pose_count, pose_rows, pose_columns] = poses.dims();
+        if image_count != pose_count
+            || !matches!((pose_rows, pose_columns), (3, 4) | (4, 4))
+        {
+            return Err(DatasetError::ShapeMismatch {
+                name: "poses".into(),
+                expected: format!(
+                    "[{}, 3, 4] or [{}, 4, 4]",
+                    image_count, image_count
+                ),
+                got: poses.dims().to_vec(),
+            });
+        }
+        if image_count != intrinsics.len() {
+            return Err(DatasetError::ShapeMismatch {
+                name: "intrinsics".into(),
+                expected: format!("[{}, 3, 3]", image_count),
+                got: vec![intrinsics.len(), 3, 3],
+            });
+        }
+        if let Some(depths) = &depths {
+            let [_, height, width, _] = images.dims();
+            if depths.dims() != [image_count, height, width] {
+                return Err(DatasetError::ShapeMismatch {
+                    name: "depths".into(),
+                    expected: format!(
+                        "[{}, {}, {}]",
+                        image_count, height, width
+                    ),
+                    got: depths.dims().to_vec(),
+                });
+            }
+        }
+        if let Some(bounds) = &bounds {
+            if bounds.len() != image_count {
+                return Err(DatasetError::ShapeMismatch {
+                    name: "bounds".into(),
+                    expected: format!("[{}, 2]", image_count),
+                    got: vec![bounds.len(), 2],
+                });
+            }
+        }
+
+        // NOTE: The values of truncated images are read as NaN,
+        // so they are skipped along with the invalid poses in lenient mode
+        let mut pose_values = poses
+            .clone()
+            .slice([0..pose_count, 0..3, 0..4])
+            .into_data()
+            .convert::<f32>()
+            .value;
+        let last_row_values = (pose_rows == 4).then(|| {
+            poses
+                .slice([0..pose_count, 3..4, 0..4])
+                .into_data()
+                .convert::<f32>()
+                .value
+        });
+        // NOTE: Only the selected images are checked and kept in the given order
+        let selected_indices = match &self.image_indices {
+            Some(indices) => {
+                if indices.is_empty() {
+                    return Err(DatasetError::InvalidConfig(
+                        "Image indices must not be empty".into(),
+                    ));
+                }
+                if let Some(index) =
+                    indices.iter().find(|index| **index >= image_count)
+                {
+                    return Err(DatasetError::InvalidConfig(format!(
+                        "Image index {} is out of the range 0..{}",
+                        index, image_count
+                    )));
+                }
+                indices.clone()
+            },
+            None => (0..image_count).collect(),
+        };
+        let mut indices = Vec::with_capacity(selected_indices.len());
+        let mut warnings = vec![];
+        for index in selected_indices {
+            let pose = &mut pose_values[index * 12..index * 12 + 12];
+            if self.invert_poses {
+                invert_pose(pose);
+            }
+            let result = match &last_row_values {
+                Some(values) => {
+                    check_last_row(&values[index * 4..index * 4 + 4], index)
+                },
+                None => Ok(()),
+            }
+            .and_then(|_| check_pose(pose, index, self.orthonormalize_poses))
+            .and_then(|_| match self.parse_mode {
+                ParseMode::Strict => Ok(()),
+                ParseMode::Lenient => check_image(&images, index),
+            });
+            match (result, &self.parse_mode) {
+                (Ok(()), _) => indices.push(index),
+                (Err(error), ParseMode::Strict) => return Err(error),
+                (Err(error), ParseMode::Lenient) => {
+                    warnings.push(ParseWarning {
+                        index,
+                        message: error.to_string(),
+                    })
+                },
+            }
+        }
+        if self.parse_mode == ParseMode::Lenient && indices.is_empty() {
+            return Err(DatasetError::Parse(format!(
+                "None of the {} images is usable",
+                image_count
+            )));
+        }
+
+        let poses = Tensor::<B, 3>::from_data(
+            Data::new(pose_values, Shape::new([image_count, 3, 4])).convert(),
+            device,
+        );
+        let is_complete = indices.iter().copied().eq(0..image_count);
+        let (images, poses, intrinsics, bounds, depths) = if is_complete {
+            (images, poses, intrinsics, bounds, depths)
+        } else {
+            let selection = Tensor::<B, 1, Int>::from_data(
+                Data::<i64, 1>::from(
+                    indices
+                        .iter()
+                        .map(|index| *index as i64)
+                        .collect::<Vec<_>>()
+                        .as_slice(),
+                )
+                .convert(),
+                device,
+            );
+            (
+                images.select(0, selection.clone()),
+                poses.select(0, selection.clone()),
+                indices.iter().map(|index| intrinsics[*index]).collect(),
+                bounds.map(|bounds| {
+                    indices.iter().map(|index| bounds[*index].clone()).collect()
+                }),
+                depths.map(|depths| depths.select(0, selection)),
+            )
+        };
+        let image_count = indices.len();
+
+        // NOTE: The depths are transformed as an extra channel of the images,
+        // so the missing measurements may be averaged in when downscaling
+        let images = match &depths {
+            Some(depths) => {
+                Tensor::cat(vec![images, depths.clone().unsqueeze_dim(3)], 3)
+            },
+            None => images,
+        };
+
+        let (images, intrinsics) = match &self.crop {
+            Some(crop) => crop_images(images, intrinsics, crop)?,
+            None => (images, intrinsics),
+        };
+        let (images, intrinsics) = match self.resize_to {
+            Some(size) => resize_images(images, intrinsics, size)?,
+            None => (images, intrinsics),
+        };
+        let (images, intrinsics) =
+            downscale_images(images, intrinsics, self.downscale)?;
+        let [_, height, width, depth_channel] = images.dims();
+        let (images, depths) = match depths {
+            Some(_) => (
+                images.clone().slice([
+                    0..image_count,
+                    0..height,
+                    0..width,
+                    0..depth_channel - 1,
+                ]),
+                Some(images.slice([
+                    0..image_count,
+                    0..height,
+                    0..width,
+                    depth_channel - 1..depth_channel,
+                ])),
+            ),
+            None => (images, None),
+        };
+
+        // NOTE: The alpha channel is kept as masks,
+        // even if the colors are composited onto the background
+        // NOTE: The grayscale images keep their single channel
+        let (images, masks) = match channel_count {
+            1 | 3 => (images, None),
+            4 => {
+                let colors = images.clone().slice([
+                    0..image_count,
+                    0..height,
+                    0..width,
+                    0..3,
+                ]);
+                let alphas =
+                    images.slice([0..image_count, 0..height, 0..width, 3..4]);
+                match self.background {
+                    Some(background) => {
+                        let background =
+                            Tensor::<B, 1>::from_floats(background, device)
+                                .reshape([1, 1, 1, 3]);
+                        (
+                            colors * alphas.clone()
+                                + (-alphas.clone() + 1.0) * background,
+                            Some(alphas),
+                        )
+                    },
+                    None => (colors, Some(alphas)),
+                }
+            },
+            _ => {
+                return Err(DatasetError::ShapeMismatch {
+                    name: "images".into(),
+                    expected: "[N, H, W, 1], [N, H, W, 3] or [N, H, W, 4]"
+                        .into(),
+                    got: images.dims().to_vec(),
+                })
+            },
+        };
+        let channel_count = images.dims()[3];
+        let masks: Vec<Option<Data<f32, 3>>> = match masks {
+            Some(masks) => masks
+                .iter_dim(0)
+                .map(|mask| Some(mask.squeeze::<3>(0).into_data().convert()))
+                .collect(),
+            None => vec![None; image_count],
+        };
+
+        // NOTE: The NDC distances are in [0, 1) from the near plane
+        // at the start of the distance range to the infinity
+        if self.ndc && self.distance_range.start <= 0.0 {
+            return Err(DatasetError::InvalidConfig(
+                "NDC rays require a positive near distance".into(),
+            ));
+        }
+        let distance_ranges = match bounds {
+            _ if self.ndc => vec![0.0..1.0; image_count],
+            Some(bounds) => bounds
+                .iter()
+                .map(check_distance_range)
+                .collect::<Result<Vec<_>, _>>()?,
+            None => {
+                vec![check_distance_range(&self.distance_range)?; image_count]
+            },
+        };
+        if self.sampling != DistanceSampling::Linear
+            && distance_ranges.iter().any(|range| range.start <= 0.0)
+        {
+            return Err(DatasetError::InvalidConfig(format!(
+                "{:?} sampling requires positive distances",
+                self.sampling
+            )));
+        }
+
+        let distance_samples = distance_ranges
+            .iter()
+            .map(|range| self.sampling.sample(range, points_per_ray))
+            .collect::<Vec<_>>();
+
+        // NOTE: Each sample is jittered within its own stratum,
+        // and the last one is bounded by the end of the range
+        let distance_widths = distance_samples
+            .iter()
+            .zip(distance_ranges.iter())
+            .map(|(samples, range)| {
+                samples
+                    .windows(2)
+                    .map(|pair| pair[1] - pair[0])
+                    .chain([range.end - samples[points_per_ray - 1]])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        if let Some(widths) = distance_widths.first() {
+            debug!("Sampling intervals of the first image: {:?}", widths);
+        }
+
+        let pose_values = poses.clone().into_data().convert::<f32>().value;
+
+        let images = match self.map_images.get::<B>()? {
+            Some(transform) => Tensor::cat(
+                images
+                    .iter_dim(0)
+                    .map(|image| {
+                        let [_, height, width, channel_count] = image.dims();
+                        let image = transform(image.squeeze::<3>(0));
+                        if image.dims() != [height, width, channel_count] {
+                            return Err(DatasetError::InvalidConfig(format!(
+                                "Image transform reshapes {:?} to {:?}",
+                                [height, width, channel_count],
+                                image.dims()
+                            )));
+                        }
+                        Ok(image.unsqueeze_dim(0))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+                0,
+            ),
+            None => images,
+        };
+
+        // NOTE: The rays are built image by image on the device,
+        // and only the rays of one image are on the device at a time
+        let build_rays = |index: usize, image: Tensor<B, 4>| {
+            let [focal_x, focal_y, center_x, center_y] = intrinsics[index];
+
+            let planes = {
+                let plane_x = ((Tensor::arange(0..width as i64, device)
+                    .float()
+                    .reshape([1, 1, width])
+                    - center_x)
+                    / focal_x)
+                    .repeat(1, height);
+                let plane_y = ((-Tensor::arange(0..height as i64, device)
+                    .float()
+                    .reshape([1, height, 1])
+                    + center_y)
+                    / focal_y)
+                    .repeat(2, width);
+                let (plane_x, plane_y) = match self.distortion {
+                    Some(distortion) => undistort(plane_x, plane_y, distortion),
+                    None => (plane_x, plane_y),
+                };
+                let (plane_y, plane_z) = match self.convention {
+                    CameraConvention::OpenGl => (
+                        plane_y,
+                        Tensor::full([1, height, width], -1.0, device),
+                    ),
+                    CameraConvention::OpenCv => (
+                        -plane_y,
+                        Tensor::full([1, height, width], 1.0, device),
+                    ),
+                };
+                Tensor::<B, 3>::stack::<4>(vec![plane_x, plane_y, plane_z], 3)
+                    .reshape([1, height, width, 1, 3])
+            };
+
+            let pose = poses.clone().slice([index..index + 1, 0..3, 0..4]);
+
+            let directions = (planes
+                * pose
+                    .clone()
+                    .slice([0..1, 0..3, 0..3])
+                    .unsqueeze_dims::<5>(&[1, 2]))
+            .sum_dim(4)
+            .swap_dims(4, 3);
+
+            let origins = pose
+                .slice([0..1, 0..3, 3..4])
+                .unsqueeze_dims::<5>(&[1, 2])
+                .swap_dims(4, 3)
+                .expand(directions.shape());
+
+            let (origins, directions) = if self.ndc {
+                ndc_rays(
+                    origins,
+                    directions,
+                    [focal_x, focal_y],
+                    self.distance_range.start as f32,
+                )
+            } else {
+                (origins, directions)
+            };
+
+            RayData {
+                directions: directions.squeeze::<4>(0).into_data(),
+                image: image.squeeze::<3>(0).into_data(),
+                origins: origins.squeeze::<4>(0).into_data(),
+            }
+        };
+
+        // NOTE: The conversions of the values, the normalization and
+        // the repetition of the directions along the rays are done on the CPU
+        let normalize_directions = self.normalize_directions;
+        let build_inner = |(index, rays, mask): (
+            usize,
+            RayData<B::FloatElem>,
+            Option<Data<f32, 3>>,
+        )| {
+            let RayData {
+                directions,
+                image,
+                origins,
+            } = rays;
+            let directions = directions.convert::<f32>();
+            let [_, _, _, dimension] = directions.shape.dims;
+            let distance_scales = normalize_directions.then(|| {
+                directions
+                    .value
+                    .chunks_exact(dimension)
+                    .map(|direction| {
+                        direction
+                            .iter()
+                            .map(|value| value * value)
+                            .sum::<f32>()
+                            .sqrt()
+                    })
+                    .collect::<Vec<_>>()
+            });
+            let directions = Data::new(
+                directions
+                    .value
+                    .chunks_exact(dimension)
+                    .enumerate()
+                    .flat_map(|(pixel, direction)| {
+                        let scale = distance_scales
+                            .as_ref()
+                            .map_or(1.0, |scales| scales[pixel]);
+                        direction
+                            .iter()
+                            .map(move |value| value / scale)
+                            .cycle()
+                            .take(direction.len() * points_per_ray)
+                    })
+                    .collect(),
+                Shape::new([height, width, points_per_ray, dimension]),
+            );
+
+            SimpleNerfDatasetInner {
+                directions,
+                distance_scales,
+                distance_widths: distance_widths[index].clone(),
+                distances: distance_samples[index]
+                    .iter()
+                    .map(|distance| *distance as f32)
+                    .collect(),
+                depth: None,
+                image: image.convert(),
+                intrinsics: intrinsics[index],
+                mask,
+                origins: origins.convert(),
+                pose: pose_values[index * 12..index * 12 + 12].to_vec(),
+            }
+        };
+
+        // NOTE: The parallel iterator keeps the order of the images
+        let inputs = images
+            .iter_dim(0)
+            .enumerate()
+            .zip(masks)
+            .map(|((index, image), mask)| {
+                (index, build_rays(index, image), mask)
+            })
+            .collect::<Vec<_>>();
+        let mut inners = if self.parallel_load {
+            inputs.into_par_iter().map(build_inner).collect::<Vec<_>>()
+        } else {
+            inputs.into_iter().map(build_inner).collect::<Vec<_>>()
+        };
+        if let Some(depths) = depths {
+            for (inner, depth) in inners.iter_mut().zip(depths.iter_dim(0)) {
+                inner.depth = Some(depth.squeeze::<3>(0).into_data().convert());
+            }
+        }
+
+        // NOTE: Nothing is printed unless a logger is initialized
+        info!(
+            "Loaded {} images of {}x{} with {} channels, \
+            {} points per ray in {:?} and {:?} sampling",
+            inners.len(),
+            width,
+            height,
+            channel_count,
+            points_per_ray,
+            self.distance_range,
+            self.sampling,
+        );
+
+        Ok(SimpleNerfDataset {
+            channel_count,
+            config: self.clone(),
+            debug_sampling: self.debug_sampling,
+            device: device.clone(),
+            distance_noise: DistanceNoise::None,
+            epoch: 0,
+            focal: intrinsics.first().map(|values| values[0]).unwrap_or(0.0),
+            height,
+            inners,
+            items: None,
+            seed: self.seed,
+            warnings,
+            width,
+        })
+    }
+
+    pub fn init_from_bytes<B: Backend>(
+        &self,
+        bytes: impl AsRef<[u8]>,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        self.init_from_reader(io::Cursor::new(bytes), device)
+    }
+
+    pub fn init_from_directory<B: Backend>(
+        &self,
+        directory: impl AsRef<Path>,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        let directory = directory.as_ref();
+
+        let read_directory_array = |name: &str| {
+            read_npy_file(directory.join(format!("{}.npy", name)), name)
+        };
+
+        self.read_arrays(read_directory_array, device)
+    }
+
+    // NOTE: The optional arrays, e.g. "masks.npy", are looked up
+    // next to the images
+    pub fn init_from_npy_files<B: Backend>(
+        &self,
+        images_path: impl AsRef<Path>,
+        poses_path: impl AsRef<Path>,
+        focal_path: impl AsRef<Path>,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        let paths = [
+            (&self.array_names.images, images_path.as_ref()),
+            (&self.array_names.poses, poses_path.as_ref()),
+            (&self.array_names.focal, focal_path.as_ref()),
+        ];
+        let directory = paths[0].1.parent().unwrap_or(Path::new(""));
+        self.read_arrays(
+            |name| match paths.iter().find(|(key, _)| key.as_str() == name) {
+                Some((_, path)) => read_npy_file(path, name),
+                None => {
+                    read_npy_file(directory.join(format!("{}.npy", name)), name)
+                },
+            },
+            device,
+        )
+    }
+
+    pub fn init_from_file_path<B: Backend>(
+        &self,
+        file_path: impl AsRef<Path>,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        self.init_from_reader(File::open(file_path)?, device)
+    }
+
+    pub fn init_from_file_path_or_url<B: Backend>(
+        &self,
+        file_path_or_url: &str,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        self.init_from_url(file_path_or_url, device)
+    }
+}
+
+// NOTE: The inverse of the Brown-Conrady model with the coefficients
+// [k1, k2, p1, p2] is solved by fixed-point iteration in the y-down plane
+fn undistort<B: Backend>(
+    plane_x: Tensor<B, 3>,
+    plane_y: Tensor<B, 3>,
+    distortion: [f32; 4],
+) -> (Tensor<B, 3>, Tensor<B, 3>) {
+    let [k1, k2, p1, p2] = distortion;
+    let distorted_x = plane_x;
+    let distorted_y = -plane_y;
+
+    let mut x = distorted_x.clone();
+    let mut y = distorted_y.clone();
+    for _ in 0..20 {
+        let xx = x.clone() * x.clone();
+        let yy = y.clone() * y.clone();
+        let xy = x.clone() * y.clone();
+        let r2 = xx.clone() + yy.clone();
+        let radial = (r2.clone() * k2 + k1) * r2.clone() + 1.0;
+        let delta_x = xy.clone() * (2.0 * p1) + (r2.clone() + xx * 2.0) * p2;
+        let delta_y = (r2 + yy * 2.0) * p1 + xy * (2.0 * p2);
+        x = (distorted_x.clone() - delta_x) / radial.clone();
+        y = (distorted_y.clone() - delta_y) / radial;
+    }
+
+    (x, -y)
+}
+
+// NOTE: The rays of [1, H, W, 1, 3] are moved to the near plane and then
+// projected, so the scene in front of it maps into [-1, 1] of each axis
+fn ndc_rays<B: Backend>(
+    origins: Tensor<B, 5>,
+    directions: Tensor<B, 5>,
+    focal: [f32; 2],
+    near: f32,
+) -> (Tensor<B, 5>, Tensor<B, 5>) {
+    let [_, height, width, _, _] = origins.dims();
+    let axis = |tensor: &Tensor<B, 5>, index: usize| {
+        tensor.clone().slice([
+            0..1,
+            0..height,
+            0..width,
+            0..1,
+            index..index + 1,
+        ])
+    };
+
+    let distances = (axis(&origins, 2) + near) / axis(&directions, 2).neg();
+    let origins = origins + directions.clone() * distances;
+    let [origin_x, origin_y, origin_z] =
+        [0, 1, 2].map(|index| axis(&origins, index));
+    let [direction_x, direction_y, direction_z] =
+        [0, 1, 2].map(|index| axis(&directions, index));
+
+    let scale_x = -2.0 * focal[0] / width as f32;
+    let scale_y = -2.0 * focal[1] / height as f32;
+    let ratio_x = origin_x / origin_z.clone();
+    let ratio_y = origin_y / origin_z.clone();
+
+    let origins = Tensor::cat(
+        vec![
+            ratio_x.clone() * scale_x,
+            ratio_y.clone() * scale_y,
+            origin_z.clone().recip() * (2.0 * near) + 1.0,
+        ],
+        4,
+    );
+    let directions = Tensor::cat(
+        vec![
+            (direction_x / direction_z.clone() - ratio_x) * scale_x,
+            (direction_y / direction_z - ratio_y) * scale_y,
+            origin_z.recip() * (-2.0 * near),
+        ],
+        4,
+    );
+
+    (origins, directions)
+}
+
+impl ImageLayout {
+    fn arrange<B: Backend>(
+        &self,
+        images: Tensor<B, 4>,
+    ) -> Result<Tensor<B, 4>, DatasetError> {
+        let [_, first, _, last] = images.dims();
+        let is_first_channel = matches!(first, 3 | 4);
+        let is_last_channel = matches!(last, 1 | 3 | 4);
+        let is_channels_first = match self {
+            ImageLayout::Detect => match (is_first_channel, is_last_channel) {
+                (true, true) => {
+                    return Err(DatasetError::InvalidConfig(format!(
+                        "Image layout of {:?} is ambiguous, \
+                         so it must be given explicitly",
+                        images.dims()
+                    )))
+                },
+                (is_first_channel, _) => is_first_channel,
+            },
+            ImageLayout::ChannelsFirst => true,
+            ImageLayout::ChannelsLast => false,
+        };
+        Ok(if is_channels_first {
+            images.permute([0, 2, 3, 1])
+        } else {
+            images
+        })
+    }
+}
+
+impl ImageTransform {
+    pub fn new<B: Backend>(
+        transform: impl Fn(Tensor<B, 3>) -> Tensor<B, 3> + Send + Sync + 'static
+    ) -> Self {
+        let transform: ImageTransformFn<B> = Arc::new(transform);
+        Self(Some(Arc::new(transform)))
+    }
+
+    fn get<B: Backend>(
+        &self
+    ) -> Result<Option<ImageTransformFn<B>>, DatasetError> {
+        self.0
+            .as_ref()
+            .map(|transform| {
+                transform
+                    .downcast_ref::<ImageTransformFn<B>>()
+                    .cloned()
+                    .ok_or_else(|| {
+                        DatasetError::InvalidConfig(
+                            "Image transform is created for another backend"
+                                .into(),
+                        )
+                    })
+            })
+            .transpose()
+    }
+}
+
+impl fmt::Debug for ImageTransform {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self.0 {
+            Some(_) => f.write_str("ImageTransform(Some(..))"),
+            None => f.write_str("ImageTransform(None)"),
+        }
+    }
+}
+
+impl Serialize for ImageTransform {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_none()
+    }
+}
+
+impl<'de> Deserialize<'de> for ImageTransform {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D
+    ) -> Result<Self, D::Error> {
+        serde::de::IgnoredAny::deserialize(deserializer)?;
+        Ok(Self::default())
+    }
+}
+
+impl DistanceNoise {
+    // NOTE: The noise is drawn as a fraction of the bin width, and
+    // the normal samples are drawn by the Box-Muller transform
+    fn sample(
+        &self,
+        rng: &mut impl Rng,
+    ) -> f32 {
+        match self {
+            DistanceNoise::Uniform {
+                scale,
+            } => rng.gen::<f32>() * scale.clamp(0.0, 1.0),
+            DistanceNoise::Gaussian {
+                std,
+            } => {
+                let radius = (-2.0 * (1.0 - rng.gen::<f32>()).ln()).sqrt();
+                let angle = std::f32::consts::TAU * rng.gen::<f32>();
+                (radius * angle.cos() * std + 0.5).clamp(0.0, 1.0)
+            },
+            DistanceNoise::None => 0.0,
+        }
+    }
+}
+
+impl DistanceSampling {
+    // NOTE: Disparity sampling is linear in the inverse distance,
+    // and log sampling is linear in the log distance
+    fn sample(
+        &self,
+        range: &Range<f64>,
+        count: usize,
+    ) -> Vec<f64> {
+        (0..count)
+            .map(|index| {
+                let ratio = index as f64 / count as f64;
+                match self {
+                    DistanceSampling::Linear => {
+                        range.start + ratio * (range.end - range.start)
+                    },
+                    DistanceSampling::Disparity => {
+                        1.0 / (1.0 / range.start
+                            + ratio * (1.0 / range.end - 1.0 / range.start))
+                    },
+                    DistanceSampling::Log => (range.start.ln()
+                        + ratio * (range.end.ln() - range.start.ln()))
+                    .exp(),
+                }
+            })
+            .collect()
+    }
+}
+
+// NOTE: The widths are narrowed by a few ulps of the far bound,
+// so that the jittered distances stay increasing and below it in f32
+fn jitter_widths(inner: &SimpleNerfDatasetInner) -> Vec<f32> {
+    let far = inner.distances.last().copied().unwrap_or_default()
+        + inner.distance_widths.last().copied().unwrap_or_default() as f32;
+    let margin = far * f32::EPSILON * 4.0;
+    inner
+        .distance_widths
+        .iter()
+        .map(|width| (*width as f32 - margin).max(0.0))
+        .collect()
+}
+
+// NOTE: The distances must be non-negative and increasing
+fn check_distance_range(
+    range: &Range<f64>
+) -> Result<Range<f64>, DatasetError> {
+    if range.start.is_nan() || range.start < 0.0 {
+        Err(DatasetError::InvalidConfig(format!(
+            "Distance range starts below zero: {:?}",
+            range
+        )))
+    } else if range.end.is_nan() || range.end <= range.start {
+        Err(DatasetError::InvalidConfig(format!(
+            "Distance range is empty or reversed: {:?}",
+            range
+        )))
+    } else {
+        Ok(range.clone())
+    }
+}
+
+fn list_image_files(directory: &Path) -> Result<Vec<PathBuf>, DatasetError> {
+    let mut image_paths = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    image_paths.retain(|path| {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        matches!(extension.as_deref(), Some("jpeg" | "jpg" | "png"))
+    });
+    image_paths.sort();
+    Ok(image_paths)
+}
+
+// NOTE: The values, the size of (width, height) and the original sizes
+type RgbImages = (Vec<f32>, (u32, u32), Vec<(usize, usize)>);
+
+// NOTE: All images must have the same size, which is returned as (width, height)
+// along with the original sizes, unless they are resized to the given size
+fn read_rgb_images(
+    image_paths: &[PathBuf],
+    resize_to: Option<(usize, usize)>,
+) -> Result<RgbImages, DatasetError> {
+    let mut images = vec![];
+    let mut size = None;
+    let mut original_sizes = vec![];
+    for image_path in image_paths.iter() {
+        let image = image::open(image_path)
+            .map_err(|error| DatasetError::Parse(error.to_string()))?
+            .into_rgb32f();
+        let (width, height) = image.dimensions();
+        original_sizes.push((width as usize, height as usize));
+        let image = resize_image(image, resize_to);
+        let dimensions = image.dimensions();
+        if *size.get_or_insert(dimensions) != dimensions {
+            return Err(DatasetError::ShapeMismatch {
+                name: format!("{:?}", image_path),
+                expected: format!(
+                    "[{}, {}, 3]",
+                    size.unwrap().1,
+                    size.unwrap().0
+                ),
+                got: vec![dimensions.1 as usize, dimensions.0 as usize, 3],
+            });
+        }
+        images.extend(image.into_raw());
+    }
+    Ok((images, size.unwrap_or_default(), original_sizes))
+}
+
+fn resize_image<P: image::Pixel<Subpixel = f32> + 'static>(
+    image: image::ImageBuffer<P, Vec<f32>>,
+    resize_to: Option<(usize, usize)>,
+) -> image::ImageBuffer<P, Vec<f32>> {
+    match resize_to {
+        Some((width, height))
+            if image.dimensions() != (width as u32, height as u32) =>
+        {
+            image::imageops::resize(
+                &image,
+                width as u32,
+                height as u32,
+                image::imageops::FilterType::Triangle,
+            )
+        },
+        _ => image,
+    }
+}
+
+// NOTE: The centered intrinsics of each image are scaled from its original size
+fn resized_intrinsics(
+    focals: impl IntoIterator<Item = f32>,
+    original_sizes: &[(usize, usize)],
+    size: (usize, usize),
+) -> Vec<[f32; 4]> {
+    focals
+        .into_iter()
+        .zip(original_sizes)
+        .map(|(focal, &(width, height))| {
+            scale_intrinsics(
+                [focal, focal, width as f32 / 2.0, height as f32 / 2.0],
+                (width, height),
+                size,
+            )
+        })
+        .collect()
+}
+
+fn find_npz_file<'a, R: io::Read + io::Seek>(
+    archive: &'a mut ZipArchive<R>,
+    name: &str,
+) -> Result<ZipFile<'a>, DatasetError> {
+    // NOTE: The names of the present arrays are listed for a missing one
+    let mut available = archive
+        .file_names()
+        .filter_map(|file_name| file_name.strip_suffix(".npy"))
+        .map(String::from)
+        .collect::<Vec<_>>();
+    available.sort();
+    archive.by_name(&npz::file_name_from_array_name(name)).map_err(|error| {
+        match error {
+            ZipError::FileNotFound => DatasetError::MissingArray {
+                name: name.into(),
+                available,
+            },
+            error => error.into(),
+        }
+    })
+}
+
+fn read_npz_array<'a, R: io::Read + io::Seek>(
+    archive: &'a mut ZipArchive<R>,
+    name: &str,
+) -> Result<NpyFile<io::BufReader<ZipFile<'a>>>, DatasetError> {
+    open_npy(io::BufReader::new(find_npz_file(archive, name)?), name)
+}
+
+fn read_npy_file(
+    file_path: impl AsRef<Path>,
+    name: &str,
+) -> Result<NpyFile<io::BufReader<File>>, DatasetError> {
+    let file = File::open(file_path).map_err(|error| match error.kind() {
+        io::ErrorKind::NotFound => DatasetError::MissingArray {
+            name: name.into(),
+            available: vec![],
+        },
+        _ => error.into(),
+    })?;
+    open_npy(io::BufReader::new(file), name)
+}
+
+// NOTE: The ragged arrays, such as images of different resolutions,
+// are stored by numpy as pickled object arrays which cannot be read
+fn open_npy<R: io::BufRead>(
+    mut reader: R,
+    name: &str,
+) -> Result<NpyFile<R>, DatasetError> {
+    let header = reader.fill_buf()?;
+    let header = &header[..header.len().min(1024)];
+    if header.windows(4).any(|window| window == b"'|O'" || window == b"\"|O\"")
+    {
+        return Err(DatasetError::Parse(format!(
+            "Array {:?} is an object array, \
+            so its items must share a shape (e.g. images of one resolution)",
+            name
+        )));
+    }
+    Ok(NpyFile::new(reader)?)
+}
+
+fn read_focals<R: io::Read>(
+    array: NpyFile<R>
+) -> Result<Vec<f32>, DatasetError> {
+    let shape =
+        array.shape().iter().map(|dim| *dim as usize).collect::<Vec<_>>();
+    let focals = read_float_values(array, "focal", &ParseMode::Strict)?;
+    if shape.len() > 1 || focals.is_empty() {
+        return Err(DatasetError::ShapeMismatch {
+            name: "focal".into(),
+            expected: "[] or [N]".into(),
+            got: shape,
+        });
+    }
+    Ok(focals)
+}
+
+fn warn_camera_angle_ignored(focal_name: &str) {
+    warn!(
+        "Both {:?} and \"camera_angle_x\" are present, and the former is used",
+        focal_name
+    );
+}
+
+// NOTE: The horizontal field of view in radians is converted to the focal
+fn read_camera_angles<B: Backend, R: io::Read>(
+    array: NpyFile<R>,
+    images: &Tensor<B, 4>,
+) -> Result<Vec<f32>, DatasetError> {
+    let [_, _, width, _] = images.dims();
+    let shape =
+        array.shape().iter().map(|dim| *dim as usize).collect::<Vec<_>>();
+    let angles =
+        read_float_values(array, "camera_angle_x", &ParseMode::Strict)?;
+    if shape.len() > 1 || angles.is_empty() {
+        return Err(DatasetError::ShapeMismatch {
+            name: "camera_angle_x".into(),
+            expected: "[] or [N]".into(),
+            got: shape,
+        });
+    }
+    angles
+        .into_iter()
+        .map(|angle| {
+            if angle > 0.0 && angle < std::f32::consts::PI {
+                Ok(0.5 * width as f32 / (0.5 * angle).tan())
+            } else {
+                Err(DatasetError::Parse(format!(
+                    "Camera angle {} is out of (0, pi)",
+                    angle
+                )))
+            }
+        })
+        .collect()
+}
+
+// NOTE: Each intrinsic matrix is reduced to [fx, fy, cx, cy]
+fn read_intrinsics<R: io::Read>(
+    array: NpyFile<R>
+) -> Result<Vec<[f32; 4]>, DatasetError> {
+    let shape =
+        array.shape().iter().map(|dim| *dim as usize).collect::<Vec<_>>();
+    if !(shape.len() == 2 || shape.len() == 3) || !shape.ends_with(&[3, 3]) {
+        return Err(DatasetError::ShapeMismatch {
+            name: "intrinsics".into(),
+            expected: "[3, 3] or [N, 3, 3]".into(),
+            got: shape,
+        });
+    }
+    Ok(read_float_values(array, "intrinsics", &ParseMode::Strict)?
+        .chunks_exact(9)
+        .map(|matrix| [matrix[0], matrix[4], matrix[2], matrix[5]])
+        .collect())
+}
+
+// NOTE: Each row is the near and far distances of an image,
+// which are named either "bounds" or "near_far"
+fn read_bounds<R: io::Read>(
+    array: NpyFile<R>,
+    name: &str,
+) -> Result<Vec<Range<f64>>, DatasetError> {
+    let shape =
+        array.shape().iter().map(|dim| *dim as usize).collect::<Vec<_>>();
+    if shape.len() != 2 || shape[1] != 2 {
+        return Err(DatasetError::ShapeMismatch {
+            name: name.into(),
+            expected: "[N, 2]".into(),
+            got: shape,
+        });
+    }
+    Ok(read_float_values(array, name, &ParseMode::Strict)?
+        .chunks_exact(2)
+        .map(|bound| bound[0] as f64..bound[1] as f64)
+        .collect())
+}
+
+fn optional<T>(
+    result: Result<T, DatasetError>
+) -> Result<Option<T>, DatasetError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(DatasetError::MissingArray {
+            ..
+        }) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+// NOTE: A single focal or intrinsic matrix is shared by all images, and the
+// focal or camera angle is only required if no intrinsic matrix is given
+fn resolve_intrinsics<B: Backend>(
+    focals: Option<Vec<f32>>,
+    intrinsics: Option<Vec<[f32; 4]>>,
+    images: &Tensor<B, 4>,
+    focal_key: &str,
+) -> Result<Vec<[f32; 4]>, DatasetError> {
+    let [image_count, height, width, _] = images.dims();
+    let focals = match focals {
+        Some(focals) if focals.len() == 1 => Some(vec![focals[0]; image_count]),
+        Some(focals) if focals.len() == image_count => Some(focals),
+        Some(focals) => {
+            return Err(DatasetError::ShapeMismatch {
+                name: "focal".into(),
+                expected: format!("[] or [{}]", image_count),
+                got: vec![focals.len()],
+            })
+        },
+        None => None,
+    };
+    let intrinsics = match intrinsics {
+        Some(intrinsics) if intrinsics.len() == 1 => {
+            Some(vec![intrinsics[0]; image_count])
+        },
+        intrinsics => intrinsics,
+    };
+
+    match (focals, intrinsics) {
+        (None, None) => Err(DatasetError::MissingArray {
+            name: format!("{} or camera_angle_x", focal_key),
+            available: vec![],
+        }),
+        (Some(focals), None) => Ok(focals
+            .into_iter()
+            .map(|focal| {
+                [focal, focal, width as f32 / 2.0, height as f32 / 2.0]
+            })
+            .collect()),
+        (None, Some(intrinsics)) => Ok(intrinsics),
+        (Some(focals), Some(intrinsics)) => {
+            let is_inconsistent = |value: f32, focal: f32| {
+                (value - focal).abs() > 1e-4 * focal.abs().max(1.0)
+            };
+            for (focal, values) in focals.iter().zip(intrinsics.iter()) {
+                if is_inconsistent(values[0], *focal)
+                    || is_inconsistent(values[1], *focal)
+                {
+                    return Err(DatasetError::Parse(format!(
+                        "Focal {} is inconsistent with the intrinsics",
+                        focal
+                    )));
+                }
+            }
+            Ok(intrinsics)
+        },
+    }
+}
+
+fn centered_intrinsics<B: Backend>(
+    focal: f32,
+    images: &Tensor<B, 4>,
+) -> Vec<[f32; 4]> {
+    let [image_count, height, width, _] = images.dims();
+    vec![[focal, focal, width as f32 / 2.0, height as f32 / 2.0]; image_count]
+}
+
+// NOTE: The last row of a homogeneous pose must be [0, 0, 0, 1]
+fn check_last_row(
+    row: &[f32],
+    index: usize,
+) -> Result<(), DatasetError> {
+    const TOLERANCE: f32 = 1e-3;
+
+    let is_homogeneous = row
+        .iter()
+        .zip([0.0, 0.0, 0.0, 1.0])
+        .all(|(value, expected)| (value - expected).abs() <= TOLERANCE);
+    if !is_homogeneous {
+        return Err(DatasetError::InvalidPose {
+            index,
+            message: format!("Its last row is {:?}", row),
+        });
+    }
+    Ok(())
+}
+
+// NOTE: Only the slice of one image is read back to look for the values
+// of a truncated image
+fn check_image<B: Backend>(
+    images: &Tensor<B, 4>,
+    index: usize,
+) -> Result<(), DatasetError> {
+    let [_, height, width, channel_count] = images.dims();
+    let values = images
+        .clone()
+        .slice([index..index + 1, 0..height, 0..width, 0..channel_count])
+        .into_data()
+        .convert::<f32>()
+        .value;
+    if values.iter().any(|value| !value.is_finite()) {
+        return Err(DatasetError::Parse(format!(
+            "Image {} has non-finite or truncated values",
+            index
+        )));
+    }
+    Ok(())
+}
+
+// NOTE: The world-to-camera pose [R | t] is inverted to [R^T | -R^T t]
+fn invert_pose(pose: &mut [f32]) {
+    let rotation =
+        [0, 1, 2].map(|row| [0, 1, 2].map(|column| pose[row * 4 + column]));
+    let translation = [pose[3], pose[7], pose[11]];
+    for row in 0..3 {
+        for column in 0..3 {
+            pose[row * 4 + column] = rotation[column][row];
+        }
+        pose[row * 4 + 3] = -(0..3)
+            .map(|index| rotation[index][row] * translation[index])
+            .sum::<f32>();
+    }
+}
+
+// NOTE: The rotations which drift from being orthonormal are rejected,
+// unless they are re-orthonormalized with the Gram-Schmidt process
+fn check_pose(
+    pose: &mut [f32],
+    index: usize,
+    orthonormalize: bool,
+) -> Result<(), DatasetError> {
+    const TOLERANCE: f32 = 1e-3;
+
+    fn dot(
+        a: [f32; 3],
+        b: [f32; 3],
+    ) -> f32 {
+        a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+    }
+
+    fn cross(
+        a: [f32; 3],
+        b: [f32; 3],
+    ) -> [f32; 3] {
+        [
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ]
+    }
+
+    fn normalize(a: [f32; 3]) -> [f32; 3] {
+        let norm = dot(a, a).sqrt();
+        a.map(|value| value / norm)
+    }
+
+    if let Some(value) = pose.iter().find(|value| !value.is_finite()) {
+        return Err(DatasetError::InvalidPose {
+            index,
+            message: format!("It has a non-finite value {}", value),
+        });
+    }
+
+    let axes = [0, 1, 2]
+        .map(|column| [pose[column], pose[4 + column], pose[8 + column]]);
+    let determinant = dot(axes[0], cross(axes[1], axes[2]));
+    if determinant <= 0.0 {
+        return Err(DatasetError::InvalidPose {
+            index,
+            message: format!(
+                "Its rotation has the determinant {}",
+                determinant
+            ),
+        });
+    }
+
+    if orthonormalize {
+        // NOTE: A scaled or sheared rotation is likely not a camera pose
+        if (determinant - 1.0).abs() > 0.1 {
+            warn!(
+                "Pose of image {} has the determinant {}, and it is \
+                 orthonormalized",
+                index, determinant
+            );
+        }
+        let x = normalize(axes[0]);
+        let y = {
+            let projection = dot(x, axes[1]);
+            normalize([0, 1, 2].map(|row| axes[1][row] - projection * x[row]))
+        };
+        let z = cross(x, y);
+        for (column, axis) in [x, y, z].iter().enumerate() {
+            for (row, value) in axis.iter().enumerate() {
+                pose[row * 4 + column] = *value;
+            }
+        }
+    } else {
+        let drift = (0..3)
+            .flat_map(|row| (0..3).map(move |column| (row, column)))
+            .map(|(row, column)| {
+                let identity = if row == column {
+                    1.0
+                } else {
+                    0.0
+                };
+                (dot(axes[row], axes[column]) - identity).abs()
+            })
+            .fold((determinant - 1.0).abs(), f32::max);
+        if drift > TOLERANCE {
+            return Err(DatasetError::InvalidPose {
+                index,
+                message: format!(
+                    "Its rotation is not orthonormal with the drift {}",
+                    drift
+                ),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// NOTE: The region of [left, top, width, height] has at least one pixel
+// unless the images are empty
+fn center_region(
+    width: usize,
+    height: usize,
+    fraction: f32,
+) -> [usize; 4] {
+    let crop_width = ((width as f32 * fraction) as usize).max(1).min(width);
+    let crop_height = ((height as f32 * fraction) as usize).max(1).min(height);
+    [
+        (width - crop_width) / 2,
+        (height - crop_height) / 2,
+        crop_width,
+        crop_height,
+    ]
+}
+
+// NOTE: The principal points are shifted with the crop,
+// so the rays of the kept pixels are unchanged
+fn crop_images<B: Backend>(
+    images: Tensor<B, 4>,
+    intrinsics: Vec<[f32; 4]>,
+    crop: &ImageCrop,
+) -> Result<(Tensor<B, 4>, Vec<[f32; 4]>), DatasetError> {
+    let [image_count, height, width, channel_count] = images.dims();
+    let [left, top, crop_width, crop_height] = match crop {
+        ImageCrop::Center(fraction) => {
+            if !(*fraction > 0.0 && *fraction <= 1.0) {
+                return Err(DatasetError::InvalidConfig(format!(
+                    "Crop fraction {} is not in (0, 1]",
+                    fraction
+                )));
+            }
+            center_region(width, height, *fraction)
+        },
+        ImageCrop::Region(region) => *region,
+    };
+    if crop_width == 0
+        || crop_height == 0
+        || left + crop_width > width
+        || top + crop_height > height
+    {
+        return Err(DatasetError::InvalidConfig(format!(
+            "Crop region {:?} is not inside the images of {}x{}",
+            [left, top, crop_width, crop_height],
+            width,
+            height
+        )));
+    }
+
+    let images = images.slice([
+        0..image_count,
+        top..top + crop_height,
+        left..left + crop_width,
+        0..channel_count,
+    ]);
+    let intrinsics = intrinsics
+        .into_iter()
+        .map(|[focal_x, focal_y, center_x, center_y]| {
+            [focal_x, focal_y, center_x - left as f32, center_y - top as f32]
+        })
+        .collect();
+
+    Ok((images, intrinsics))
+}
+
+// NOTE: The size is (width, height), and the images are bilinearly resized
+fn resize_images<B: Backend>(
+    images: Tensor<B, 4>,
+    intrinsics: Vec<[f32; 4]>,
+    size: (usize, usize),
+) -> Result<(Tensor<B, 4>, Vec<[f32; 4]>), DatasetError> {
+    let [_, height, width, _] = images.dims();
+    let (target_width, target_height) = size;
+    if target_width == 0 || target_height == 0 {
+        return Err(DatasetError::InvalidConfig(format!(
+            "Resized images of {}x{} are empty",
+            target_width, target_height
+        )));
+    }
+    if (target_width, target_height) == (width, height) {
+        return Ok((images, intrinsics));
+    }
+
+    let images = interpolate(
+        images.swap_dims(1, 3),
+        [target_width, target_height],
+        InterpolateOptions::new(InterpolateMode::Bilinear),
+    )
+    .swap_dims(1, 3);
+    let intrinsics = intrinsics
+        .into_iter()
+        .map(|values| scale_intrinsics(values, (width, height), size))
+        .collect();
+
+    Ok((images, intrinsics))
+}
+
+fn scale_intrinsics(
+    [focal_x, focal_y, center_x, center_y]: [f32; 4],
+    (width, height): (usize, usize),
+    (target_width, target_height): (usize, usize),
+) -> [f32; 4] {
+    let scale_x = target_width as f32 / width as f32;
+    let scale_y = target_height as f32 / height as f32;
+    [
+        focal_x * scale_x,
+        focal_y * scale_y,
+        center_x * scale_x,
+        center_y * scale_y,
+    ]
+}
+
+// NOTE: The images are average pooled by blocks,
+// so the factor must divide both dimensions
+fn downscale_images<B: Backend>(
+    images: Tensor<B, 4>,
+    intrinsics: Vec<[f32; 4]>,
+    factor: usize,
+) -> Result<(Tensor<B, 4>, Vec<[f32; 4]>), DatasetError> {
+    let [_, height, width, _] = images.dims();
+    if factor == 0 {
+        return Err(DatasetError::InvalidConfig(
+            "Downscale factor must be positive".into(),
+        ));
+    }
+    if factor > height || factor > width {
+        return Err(DatasetError::InvalidConfig(format!(
+            "Downscale factor {} is larger than the images of {}x{}",
+            factor, width, height
+        )));
+    }
+    if height % factor != 0 || width % factor != 0 {
+        return Err(DatasetError::InvalidConfig(format!(
+            "Downscale factor {} does not divide the images of {}x{}",
+            factor, width, height
+        )));
+    }
+    if factor == 1 {
+        return Ok((images, intrinsics));
+    }
+
+    let images = avg_pool2d(
+        images.swap_dims(1, 3),
+        [factor, factor],
+        [factor, factor],
+        [0, 0],
+        false,
+    )
+    .swap_dims(1, 3);
+
+    // NOTE: Each pooled pixel is at the center of its block
+    let scale = factor as f32;
+    let offset = (scale - 1.0) / 2.0;
+    let intrinsics = intrinsics
+        .into_iter()
+        .map(|[focal_x, focal_y, center_x, center_y]| {
+            [
+                focal_x / scale,
+                focal_y / scale,
+                (center_x - offset) / scale,
+                (center_y - offset) / scale,
+            ]
+        })
+        .collect();
+
+    Ok((images, intrinsics))
+}
+
+// NOTE: The missing values of a truncated array are filled with NaN
+fn read_images<B: Backend, R: io::Read>(
+    array: NpyFile<R>,
+    device: &B::Device,
+    mode: &ParseMode,
+) -> Result<Tensor<B, 4>, DatasetError> {
+    let shape = to_shape(array.shape(), "images", "[N, H, W, C]")?;
+    let mut values = read_image_values(array, "images", mode)?;
+    values.resize(shape.num_elements(), f32::NAN);
+    Ok(Tensor::from_data(Data::new(values, shape).convert(), device))
+}
+
+// NOTE: The depths are in meters, and zero means no measurement
+fn read_depths<B: Backend, R: io::Read>(
+    array: NpyFile<R>,
+    device: &B::Device,
+) -> Result<Tensor<B, 3>, DatasetError> {
+    let shape = to_shape(array.shape(), "depths", "[N, H, W]")?;
+    Ok(Tensor::from_data(
+        Data::new(
+            read_float_values(array, "depths", &ParseMode::Strict)?,
+            shape,
+        )
+        .convert(),
+        device,
+    ))
+}
+
+// NOTE: The masks of u8 are scaled like the images, so 255 is fully covered
+fn read_masks<B: Backend, R: io::Read>(
+    array: NpyFile<R>,
+    device: &B::Device,
+) -> Result<Tensor<B, 3>, DatasetError> {
+    let shape = to_shape(array.shape(), "masks", "[N, H, W]")?;
+    Ok(Tensor::from_data(
+        Data::new(
+            read_image_values(array, "masks", &ParseMode::Strict)?,
+            shape,
+        )
+        .convert(),
+        device,
+    ))
+}
+
+// NOTE: The masks become the alpha channel of the images,
+// which replace the existing one if any
+fn attach_masks<B: Backend>(
+    images: Tensor<B, 4>,
+    masks: Tensor<B, 3>,
+) -> Result<Tensor<B, 4>, DatasetError> {
+    let [image_count, height, width, channel_count] = images.dims();
+    if masks.dims() != [image_count, height, width] {
+        return Err(DatasetError::ShapeMismatch {
+            name: "masks".into(),
+            expected: format!("[{}, {}, {}]", image_count, height, width),
+            got: masks.dims().to_vec(),
+        });
+    }
+    let colors = match channel_count {
+        3 => images,
+        4 => images.slice([0..image_count, 0..height, 0..width, 0..3]),
+        _ => {
+            return Err(DatasetError::ShapeMismatch {
+                name: "images".into(),
+                expected: "[N, H, W, 3] or [N, H, W, 4] with masks".into(),
+                got: images.dims().to_vec(),
+            })
+        },
+    };
+    Ok(Tensor::cat(vec![colors, masks.unsqueeze_dim(3)], 3))
+}
+
+fn read_poses<B: Backend, R: io::Read>(
+    array: NpyFile<R>,
+    device: &B::Device,
+    mode: &ParseMode,
+) -> Result<Tensor<B, 3>, DatasetError> {
+    // NOTE: The flattened poses of [N, 16] are row-major 4x4 matrices
+    let dims = array.shape().to_vec();
+    let shape = match dims.as_slice() {
+        [count, 3, 4] => Shape::new([*count as usize, 3, 4]),
+        [count, 4, 4] | [count, 16] => Shape::new([*count as usize, 4, 4]),
+        _ => {
+            return Err(DatasetError::ShapeMismatch {
+                name: "poses".into(),
+                expected: "[N, 3, 4], [N, 4, 4] or [N, 16]".into(),
+                got: dims.iter().map(|dim| *dim as usize).collect(),
+            })
+        },
+    };
+    let mut values = read_float_values(array, "poses", mode)?;
+    values.resize(shape.num_elements(), f32::NAN);
+    Ok(Tensor::from_data(Data::new(values, shape).convert(), device))
+}
+
+fn to_shape<const D: usize>(
+    dims: &[u64],
+    name: &str,
+    expected: &str,
+) -> Result<Shape<D>, DatasetError> {
+    let dims = dims.iter().map(|dim| *dim as usize).collect::<Vec<_>>();
+    match <[usize; D]>::try_from(dims.as_slice()) {
+        Ok(dims) => Ok(Shape::new(dims)),
+        Err(_) => Err(DatasetError::ShapeMismatch {
+            name: name.into(),
+            expected: expected.into(),
+            got: dims,
+        }),
+    }
+}
+
+fn read_float_values<R: io::Read>(
+    array: NpyFile<R>,
+    name: &str,
+    mode: &ParseMode,
+) -> Result<Vec<f32>, DatasetError> {
+    let dtype = array.dtype();
+    if let DType::Plain(kind) = &dtype {
+        match (kind.type_char(), kind.size_field()) {
+            (TypeChar::Float, 4) => {
+                return collect_values(array.data::<f32>()?, mode)
+            },
+            (TypeChar::Float, 8) => {
+                return collect_values(
+                    array.data::<f64>()?.map(|value| Ok(value? as f32)),
+                    mode,
+                )
+            },
+            _ => {},
+        }
+    }
+
+    Err(DatasetError::Parse(format!(
+        "Array {:?} has an unsupported dtype: {}",
+        name,
+        dtype.descr()
+    )))
+}
+
+// NOTE: Unsigned integer values are normalized to the range of [0, 1]
+fn read_image_values<R: io::Read>(
+    array: NpyFile<R>,
+    name: &str,
+    mode: &ParseMode,
+) -> Result<Vec<f32>, DatasetError> {
+    if let DType::Plain(kind) = &array.dtype() {
+        match (kind.type_char(), kind.size_field()) {
+            (TypeChar::Uint, 1) => {
+                return collect_values(
+                    array.data::<u8>()?.map(|value| Ok(value? as f32 / 255.0)),
+                    mode,
+                )
+            },
+            (TypeChar::Uint, 2) => {
+                return collect_values(
+                    array
+                        .data::<u16>()?
+                        .map(|value| Ok(value? as f32 / 65535.0)),
+                    mode,
+                )
+            },
+            _ => {},
+        }
+    }
+
+    read_float_values(array, name, mode)
+}
+
+// NOTE: The values before a truncation are kept in lenient mode
+fn collect_values(
+    values: impl Iterator<Item = io::Result<f32>>,
+    mode: &ParseMode,
+) -> Result<Vec<f32>, DatasetError> {
+    match mode {
+        ParseMode::Strict => Ok(values.collect::<io::Result<_>>()?),
+        ParseMode::Lenient => Ok(values.map_while(Result::ok).collect()),
+    }
+}
+
+impl<B: Backend> SimpleNerfDataset<B> {
+    pub fn iter(&self) -> SimpleNerfDatasetIter<'_, B> {
+        SimpleNerfDatasetIter {
+            dataset: self,
+            index: 0,
+        }
+    }
+
+    pub fn focal(&self) -> f32 {
+        self.focal
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    // NOTE: The size is (height, width) in pixels
+    pub fn image_size(&self) -> (usize, usize) {
+        (self.height, self.width)
+    }
+
+    pub fn channel_count(&self) -> usize {
+        self.channel_count
+    }
+
+    pub fn device(&self) -> &B::Device {
+        &self.device
+    }
+
+    // NOTE: The inners are kept as data, so only the items built later
+    // are on the new device
+    pub fn to_device(
+        self,
+        device: &B::Device,
+    ) -> SimpleNerfDataset<B> {
+        SimpleNerfDataset {
+            device: device.clone(),
+            ..self
+        }
+    }
+
+    // NOTE: The jitter of each item is derived from the seed, the index and
+    // the epoch, so the same calls yield the same distances
+    pub fn with_seed(
+        self,
+        seed: u64,
+    ) -> SimpleNerfDataset<B> {
+        SimpleNerfDataset {
+            seed: Some(seed),
+            ..self
+        }
+    }
+
+    pub fn set_epoch(
+        &mut self,
+        epoch: usize,
+    ) {
+        self.epoch = epoch;
+    }
+
+    // NOTE: The images skipped in lenient mode are indexed as in the source
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    // NOTE: The rows of the camera-to-world matrix without a device
+    pub fn pose(
+        &self,
+        index: usize,
+    ) -> Option<[[f32; 4]; 3]> {
+        let pose = &self.inners.get(index)?.pose;
+        Some(
+            [0, 1, 2]
+                .map(|row| [0, 1, 2, 3].map(|column| pose[row * 4 + column])),
+        )
+    }
+
+    // NOTE: The pose is the camera-to-world matrix of [3, 4]
+    pub fn pose_tensor(
+        &self,
+        index: usize,
+    ) -> Option<Tensor<B, 2>> {
+        let inner = self.inners.get(index)?;
+        Some(Tensor::from_data(
+            Data::new(inner.pose.clone(), Shape::new([3, 4])).convert(),
+            &self.device,
+        ))
+    }
+
+    // NOTE: The images are permuted deterministically for the seed,
+    // so that a following ratio split is representative
+    pub fn shuffle(
+        &mut self,
+        seed: u64,
+    ) {
+        self.inners.shuffle(&mut StdRng::seed_from_u64(seed));
+        self.items = None;
+    }
+
+    // NOTE: An empty dataset is split into two empty datasets,
+    // and the train split is noisy as usual
+    pub fn split_for_training(
+        self,
+        ratio: f32,
+    ) -> SimpleNerfDatasetSplit<B> {
+        let train_count = ((ratio.clamp(0.0, 1.0) * self.len() as f32).round()
+            as usize)
+            .min(self.len());
+        let (inners_train, inners_test) = self.inners.split_at(train_count);
+
+        SimpleNerfDatasetSplit {
+            test: self.with_inners(inners_test.into(), DistanceNoise::None),
+            train: self
+                .with_inners(inners_train.into(), self.train_distance_noise()),
+        }
+    }
+
+    // NOTE: The out-of-range and repeated indices are ignored,
+    // and the images keep their order in both splits
+    pub fn split_by_indices(
+        self,
+        train_indices: &[usize],
+    ) -> SimpleNerfDatasetSplit<B> {
+        let (inners_train, inners_test): (Vec<_>, Vec<_>) = self
+            .inners
+            .iter()
+            .enumerate()
+            .partition(|(index, _)| train_indices.contains(index));
+        let into_inners = |inners: Vec<(usize, &SimpleNerfDatasetInner)>| {
+            inners.into_iter().map(|(_, inner)| inner.clone()).collect()
+        };
+
+        SimpleNerfDatasetSplit {
+            test: self
+                .with_inners(into_inners(inners_test), DistanceNoise::None),
+            train: self.with_inners(
+                into_inners(inners_train),
+                self.train_distance_noise(),
+            ),
+        }
+    }
+
+    // NOTE: The coverage is the mean of a mask,
+    // and the images without masks are always kept
+    pub fn filter_by_mask_coverage(
+        self,
+        min_fraction: f32,
+    ) -> SimpleNerfDataset<B> {
+        let inners = self
+            .inners
+            .iter()
+            .filter(|inner| match &inner.mask {
+                Some(mask) => {
+                    let coverage = mask.value.iter().sum::<f32>()
+                        / mask.value.len().max(1) as f32;
+                    coverage >= min_fraction
+                },
+                None => true,
+            })
+            .cloned()
+            .collect();
+
+        self.with_inners(inners, self.distance_noise.clone())
+    }
+
+    // NOTE: The items are cached only if the distances are not noisy,
+    // since the jitter must vary on every access during training
+    pub fn cached(self) -> SimpleNerfDataset<B> {
+        if self.is_stratified() {
+            return self;
+        }
+
+        let items =
+            Some((0..self.len()).filter_map(|index| self.get(index)).collect());
+        SimpleNerfDataset {
+            items,
+            ..self
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inners.is_empty()
+    }
+
+    pub fn is_cached(&self) -> bool {
+        self.items.is_some()
+    }
+
+    pub fn distance_noise(&self) -> &DistanceNoise {
+        &self.distance_noise
+    }
+
+    // NOTE: The noise can be annealed between epochs of training
+    pub fn set_distance_noise(
+        &mut self,
+        distance_noise: DistanceNoise,
+    ) {
+        self.distance_noise = distance_noise;
+        self.items = None;
+    }
+
+    pub fn has_noisy_distance(&self) -> bool {
+        self.distance_noise != DistanceNoise::None
+    }
+
+    // NOTE: The noise set on this dataset is kept for the train split,
+    // and a dataset without noise takes the one of the config
+    fn train_distance_noise(&self) -> DistanceNoise {
+        if self.has_noisy_distance() {
+            self.distance_noise.clone()
+        } else {
+            self.config.distance_noise.clone()
+        }
+    }
+
+    fn is_stratified(&self) -> bool {
+        self.has_noisy_distance()
+            && self.config.sampling_strategy == SamplingStrategy::Stratified
+    }
+
+    // NOTE: The sampling intervals of an item are only traced
+    // if the sampling is debugged
+    fn sampling_message(
+        &self,
+        index: usize,
+    ) -> Option<String> {
+        let inner = self.inners.get(index)?;
+        self.debug_sampling.then(|| {
+            format!(
+                "Sampling intervals of item {}: {:?}",
+                index, inner.distance_widths
+            )
+        })
+    }
+
+    fn with_inners(
+        &self,
+        inners: Vec<SimpleNerfDatasetInner>,
+        distance_noise: DistanceNoise,
+    ) -> SimpleNerfDataset<B> {
+        SimpleNerfDataset {
+            channel_count: self.channel_count,
+            config: self.config.clone(),
+            debug_sampling: self.debug_sampling,
+            device: self.device.clone(),
+            distance_noise,
+            epoch: self.epoch,
+            focal: self.focal,
+            height: self.height,
+            inners,
+            items: None,
+            seed: self.seed,
+            warnings: self.warnings.clone(),
+            width: self.width,
+        }
+    }
+
+    // NOTE: The distances are noisy only if both datasets opt in, and
+    // the warnings of each dataset keep the indices of its own source
+    pub fn concat(
+        self,
+        other: SimpleNerfDataset<B>,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        if self.device != other.device {
+            return Err(DatasetError::InvalidConfig(format!(
+                "Datasets are on different devices: {:?} and {:?}",
+                self.device, other.device
+            )));
+        }
+        if (self.height, self.width, self.channel_count)
+            != (other.height, other.width, other.channel_count)
+        {
+            return Err(DatasetError::ShapeMismatch {
+                name: "images".into(),
+                expected: format!(
+                    "[N, {}, {}, {}]",
+                    self.height, self.width, self.channel_count
+                ),
+                got: vec![
+                    other.len(),
+                    other.height,
+                    other.width,
+                    other.channel_count,
+                ],
+            });
+        }
+        let points_per_ray = |dataset: &SimpleNerfDataset<B>| {
+            dataset.inners.first().map(|inner| inner.distance_widths.len())
+        };
+        if let (Some(points_per_ray), Some(other_points_per_ray)) =
+            (points_per_ray(&self), points_per_ray(&other))
+        {
+            if points_per_ray != other_points_per_ray {
+                return Err(DatasetError::ShapeMismatch {
+                    name: "distances".into(),
+                    expected: format!(
+                        "[N, {}, {}, {}, 1]",
+                        self.height, self.width, points_per_ray
+                    ),
+                    got: vec![
+                        other.len(),
+                        other.height,
+                        other.width,
+                        other_points_per_ray,
+                        1,
+                    ],
+                });
+            }
+        }
+        // NOTE: The config of this dataset is kept, so the fields which
+        // change how the rays are built or sampled must agree
+        let config = serde_json::to_value(&self.config)?;
+        let other_config = serde_json::to_value(&other.config)?;
+        if let Some(field) = [
+            "background",
+            "convention",
+            "distance_range",
+            "distortion",
+            "invert_poses",
+            "ndc",
+            "normalize_directions",
+            "orthonormalize_poses",
+            "sampling",
+            "sampling_strategy",
+        ]
+        .iter()
+        .find(|field| config[**field] != other_config[**field])
+        {
+            return Err(DatasetError::InvalidConfig(format!(
+                "Datasets are loaded with different {:?}: {} and {}",
+                field, config[*field], other_config[*field]
+            )));
+        }
+        let distance_noise =
+            if self.has_noisy_distance() && other.has_noisy_distance() {
+                self.distance_noise
+            } else {
+                DistanceNoise::None
+            };
+
+        let mut inners = self.inners;
+        inners.extend(other.inners);
+        let mut warnings = self.warnings;
+        warnings.extend(other.warnings);
+
+        Ok(SimpleNerfDataset {
+            channel_count: self.channel_count,
+            config: self.config,
+            debug_sampling: self.debug_sampling || other.debug_sampling,
+            device: self.device,
+            distance_noise,
+            epoch: self.epoch,
+            focal: self.focal,
+            height: self.height,
+            inners,
+            items: None,
+            seed: self.seed,
+            warnings,
+            width: self.width,
+        })
+    }
+}
+
+impl<B: Backend> Dataset<SimpleNerfData> for SimpleNerfDataset<B> {
+    fn len(&self) -> usize {
+        self.inners.len()
+    }
+
+    fn get(
+        &self,
+        index: usize,
+    ) -> Option<SimpleNerfData> {
+        if let Some(items) = &self.items {
+            return items.get(index).cloned();
+        }
+
+        // NOTE: The inner is borrowed and only the needed fields are cloned,
+        // so that no field is moved out before the others are read
+        let inner = self.inners.get(index)?;
+
+        let directions =
+            Tensor::from_data(inner.directions.clone().convert(), &self.device);
+        // NOTE: The distances are shared by all pixels of an image,
+        // so they are stored once and expanded here
+        let distances = Tensor::<B, 4>::from_data(
+            Data::new(
+                inner.distances.clone(),
+                Shape::new([1, 1, inner.distances.len(), 1]),
+            )
+            .convert(),
+            &self.device,
+        )
+        .repeat(0, self.height)
+        .repeat(1, self.width);
+        let origins =
+            Tensor::from_data(inner.origins.clone().convert(), &self.device);
+
+        let mut distances = distances;
+        if self.is_stratified() {
+            if let Some(message) = self.sampling_message(index) {
+                trace!("{}", message);
+            }
+            let shape = distances.shape();
+            let noises = match self.seed {
+                Some(seed) => {
+                    let mut rng = {
+                        let mut seed_bytes = [0; 32];
+                        seed_bytes[0..8].copy_from_slice(&seed.to_le_bytes());
+                        seed_bytes[8..16]
+                            .copy_from_slice(&(index as u64).to_le_bytes());
+                        seed_bytes[16..24].copy_from_slice(
+                            &(self.epoch as u64).to_le_bytes(),
+                        );
+                        StdRng::from_seed(seed_bytes)
+                    };
+                    Tensor::from_data(
+                        Data::<f32, 4>::new(
+                            (0..shape.num_elements())
+                                .map(|_| self.distance_noise.sample(&mut rng))
+                                .collect(),
+                            shape,
+                        )
+                        .convert(),
+                        &self.device,
+                    )
+                },
+                None => match self.distance_noise {
+                    DistanceNoise::Uniform {
+                        scale,
+                    } => {
+                        distances.random_like(Distribution::Uniform(0.0, 1.0))
+                            * scale.clamp(0.0, 1.0)
+                    },
+                    DistanceNoise::Gaussian {
+                        std,
+                    } => (distances
+                        .random_like(Distribution::Normal(0.0, 1.0))
+                        * std
+                        + 0.5)
+                        .clamp(0.0, 1.0),
+                    DistanceNoise::None => distances.zeros_like(),
+                },
+            };
+            let widths = jitter_widths(inner);
+            let widths = Tensor::<B, 1>::from_data(
+                Data::<f32, 1>::new(widths.clone(), Shape::new([widths.len()]))
+                    .convert(),
+                &self.device,
+            )
+            .reshape([1, 1, widths.len(), 1]);
+            let noises = noises * widths;
+            distances = distances + noises;
+        }
+        // NOTE: The distances are scaled by the norms of the directions,
+        // so that the positions and the intervals are in metric length
+        if let Some(scales) = &inner.distance_scales {
+            distances = distances
+                * Tensor::<B, 4>::from_data(
+                    Data::new(
+                        scales.clone(),
+                        Shape::new([self.height, self.width, 1, 1]),
+                    )
+                    .convert(),
+                    &self.device,
+                );
+        }
+
+        let image = inner.image.clone();
+
+        let intervals = {
+            let [height, width, points_per_ray, ..] = distances.dims();
+            Tensor::cat(
+                vec![
+                    distances.clone().slice([
+                        0..height,
+                        0..width,
+                        1..points_per_ray,
+                    ]) - distances.clone().slice([
+                        0..height,
+                        0..width,
+                        0..(points_per_ray - 1),
+                    ]),
+                    Tensor::full([height, width, 1, 1], 1e9, &self.device),
+                ],
+                2,
+            )
+        };
+
+        let positions: Tensor<B, 4> =
+            origins + directions.clone() * distances.clone();
+
+        let directions = directions.into_data().convert();
+        let distances = distances.into_data().convert();
+        let intervals = intervals.into_data().convert();
+        let positions = positions.into_data().convert();
+
+        Some(SimpleNerfData {
+            depth: inner.depth.clone(),
+            directions,
+            distances,
+            image,
+            intervals,
+            mask: inner.mask.clone(),
+            positions,
+        })
+    }
+}
+
+impl<B: Backend> Iterator for SimpleNerfDatasetIter<'_, B> {
+    type Item = SimpleNerfData;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.dataset.get(self.index)?;
+        self.index += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.dataset.len().saturating_sub(self.index);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<B: Backend> ExactSizeIterator for SimpleNerfDatasetIter<'_, B> {}
+
+impl<'a, B: Backend> IntoIterator for &'a SimpleNerfDataset<B> {
+    type IntoIter = SimpleNerfDatasetIter<'a, B>;
+    type Item = SimpleNerfData;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<B: Backend> SimpleNerfInput<B> {
+    pub fn from_data(
+        data: SimpleNerfData,
+        device: &B::Device,
+    ) -> SimpleNerfInput<B> {
+        SimpleNerfInput {
+            depth: data
+                .depth
+                .map(|depth| Tensor::from_data(depth.convert(), device)),
+            directions: Tensor::from_data(data.directions.convert(), device),
+            distances: Tensor::from_data(data.distances.convert(), device),
+            image: Tensor::from_data(data.image.convert(), device),
+            intervals: Tensor::from_data(data.intervals.convert(), device),
+            mask: data
+                .mask
+                .map(|mask| Tensor::from_data(mask.convert(), device)),
+            positions: Tensor::from_data(data.positions.convert(), device),
+        }
+    }
+
+    // NOTE: The rays are selected without replacement,
+    // so no more than all pixel rays can be sampled
+    pub fn sample_rays(
+        &self,
+        count: usize,
+        seed: u64,
+    ) -> SimpleNerfInput<B> {
+        let [height, width, points_per_ray, _] = self.positions.dims();
+        let channel_count = self.image.dims()[2];
+        let ray_count = height * width;
+        let count = count.min(ray_count);
+
+        let indices = {
+            let mut rng = StdRng::seed_from_u64(seed);
+            Tensor::<B, 1, Int>::from_data(
+                Data::<i64, 1>::new(
+                    rand::seq::index::sample(&mut rng, ray_count, count)
+                        .into_iter()
+                        .map(|index| index as i64)
+                        .collect(),
+                    Shape::new([count]),
+                )
+                .convert(),
+                &self.positions.device(),
+            )
+        };
+
+        let rays = self.clone().into_rays();
+        SimpleNerfInput {
+            depth: rays.depth.map(|depth| {
+                depth.select(0, indices.clone()).reshape([count, 1, 1])
+            }),
+            directions: rays.directions.select(0, indices.clone()).reshape([
+                count,
+                1,
+                points_per_ray,
+                3,
+            ]),
+            distances: rays.distances.select(0, indices.clone()).reshape([
+                count,
+                1,
+                points_per_ray,
+                1,
+            ]),
+            image: rays.image.select(0, indices.clone()).reshape([
+                count,
+                1,
+                channel_count,
+            ]),
+            intervals: rays.intervals.select(0, indices.clone()).reshape([
+                count,
+                1,
+                points_per_ray,
+                1,
+            ]),
+            mask: rays.mask.map(|mask| {
+                mask.select(0, indices.clone()).reshape([count, 1, 1])
+            }),
+            positions: rays.positions.select(0, indices).reshape([
+                count,
+                1,
+                points_per_ray,
+                3,
+            ]),
+        }
+    }
+
+    pub fn into_rays(self) -> RayBatch<B> {
+        let [height, width, points_per_ray, _] = self.positions.dims();
+        let channel_count = self.image.dims()[2];
+        let ray_count = height * width;
+        RayBatch {
+            depth: self.depth.map(|depth| depth.reshape([ray_count, 1])),
+            directions: self.directions.reshape([ray_count, points_per_ray, 3]),
+            distances: self.distances.reshape([ray_count, points_per_ray, 1]),
+            image: self.image.reshape([ray_count, channel_count]),
+            intervals: self.intervals.reshape([ray_count, points_per_ray, 1]),
+            mask: self.mask.map(|mask| mask.reshape([ray_count, 1])),
+            positions: self.positions.reshape([ray_count, points_per_ray, 3]),
+        }
+    }
+
+    // NOTE: The distances of [H, W, F, 1] are taken along the rays of
+    // the item, e.g. the fine distances resampled from the coarse weights
+    pub fn positions_at(
+        &self,
+        distances: Tensor<B, 4>,
+    ) -> Tensor<B, 4> {
+        let [height, width, _, _] = self.positions.dims();
+        let directions =
+            self.directions.clone().slice([0..height, 0..width, 0..1, 0..3]);
+        let origins =
+            self.positions.clone().slice([0..height, 0..width, 0..1, 0..3])
+                - directions.clone()
+                    * self.distances.clone().slice([0..height, 0..width, 0..1]);
+
+        origins + directions * distances
+    }
+
+    // NOTE: The target colors are flattened in the same order as
+    // `into_rays`, so the color at index `y * width + x` belongs to
+    // the ray cast through the pixel `(x, y)`, and the alpha is dropped
+    pub fn target_rgb(&self) -> Tensor<B, 2> {
+        let [height, width, channel_count] = self.image.dims();
+        let ray_count = height * width;
+        self.image
+            .clone()
+            .reshape([ray_count, channel_count])
+            .slice([0..ray_count, 0..channel_count.min(3)])
+    }
+}
+
+impl SimpleNerfData {
+    pub fn into_input<B: Backend>(
+        self,
+        device: &B::Device,
+    ) -> SimpleNerfInput<B> {
+        SimpleNerfInput::from_data(self, device)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use npyz::{AutoSerialize, WriteOptions, WriterBuilder};
+    use std::io::Write;
+    use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+    type Backend = burn::backend::Wgpu;
+
+    pub(super) const TEST_DATA_FILE_PATH: &str = "resources/lego-tiny/data.npz";
+    pub(super) const TEST_DATA_URL: &str =
+        "https://raw.githubusercontent.com/AsherJingkongChen/simple-nerf-rust/main/resources/lego-tiny/data.npz";
+
+    pub(super) fn npy_bytes<T: AutoSerialize>(
+        shape: &[u64],
+        values: Vec<T>,
+    ) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut writer = WriteOptions::new()
+            .default_dtype()
+            .shape(shape)
+            .writer(&mut bytes)
+            .begin_nd()
+            .unwrap();
+        writer.extend(values).unwrap();
+        writer.finish().unwrap();
+        bytes
+    }
+
+    pub(super) fn npz_bytes(arrays: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        npz_bytes_with(arrays, CompressionMethod::Stored)
+    }
+
+    // NOTE: `np.savez_compressed` deflates the members,
+    // while `np.savez` stores them
+    pub(super) fn npz_bytes_with(
+        arrays: &[(&str, Vec<u8>)],
+        method: CompressionMethod,
+    ) -> Vec<u8> {
+        let mut writer = ZipWriter::new(io::Cursor::new(vec![]));
+        for (name, bytes) in arrays {
+            writer
+                .start_file(
+                    npz::file_name_from_array_name(name),
+                    SimpleFileOptions::default().compression_method(method),
+                )
+                .unwrap();
+            writer.write_all(bytes).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    pub(super) fn tar_gz_bytes(arrays: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut builder =
+            tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+        for (name, bytes) in arrays {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(
+                    &mut header,
+                    npz::file_name_from_array_name(name),
+                    bytes.as_slice(),
+                )
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    pub(super) fn identity_poses(count: usize) -> Vec<f32> {
+        [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 4.0]
+            .repeat(count)
+    }
+
+    // NOTE: The directory is unique to the process and starts empty,
+    // so concurrent test runs do not share their files
+    pub(super) fn temp_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "simple-nerf-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn simple_nerf_dataset_output_shape() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        let item = dataset.get(0);
+        assert!(item.is_some());
+
+        let item = item.unwrap();
+        assert_eq!(item.directions.shape.dims, [100, 100, 7, 3]);
+        assert_eq!(item.distances.shape.dims, [100, 100, 7, 1]);
+        assert_eq!(item.image.shape.dims, [100, 100, 3]);
+        assert_eq!(item.intervals.shape.dims, [100, 100, 7, 1]);
+        assert_eq!(item.positions.shape.dims, [100, 100, 7, 3]);
+        assert_eq!(item.positions.shape.dims, item.directions.shape.dims);
+
+        let inners = dataset.inners.clone();
+        assert_eq!(inners.len(), 106);
+
+        let inner = inners.first();
+        assert!(inner.is_some());
+
+        let inner = inner.unwrap();
+        assert_eq!(inner.directions.shape.dims, [100, 100, 7, 3]);
+        assert_eq!(inner.distances.len(), 7);
+        assert_eq!(inner.image.shape.dims, [100, 100, 3]);
+        assert_eq!(inner.origins.shape.dims, [100, 100, 1, 3]);
+        assert_eq!(dataset.channel_count, 3);
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![138.88887_f32])),
+            ("images", npy_bytes(&[2, 100, 100, 1], vec![0.5_f32; 20000])),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!(dataset.channel_count(), 1);
+
+        let item = dataset.get(0).unwrap();
+        assert_eq!(item.directions.shape.dims, [100, 100, 7, 3]);
+        assert_eq!(item.image.shape.dims, [100, 100, 1]);
+        assert_eq!(item.positions.shape.dims, [100, 100, 7, 3]);
+
+        let input = item.into_input::<Backend>(&device);
+        assert_eq!(input.sample_rays(16, 0).image.dims(), [16, 1, 1]);
+        assert_eq!(input.into_rays().image.dims(), [10000, 1]);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_in_memory_loading() {
+        let device = Default::default();
+
+        let bytes = std::fs::read(TEST_DATA_FILE_PATH).unwrap();
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_bytes::<Backend>(bytes, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset_from_file = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        assert_eq!(
+            dataset.unwrap().inners.len(),
+            dataset_from_file.inners.len()
+        );
+
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_bytes::<Backend>(
+                include_bytes!("../../resources/lego-tiny/data.npz"),
+                &device,
+            );
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+        assert_eq!(
+            dataset.unwrap().inners.len(),
+            dataset_from_file.inners.len()
+        );
+    }
+
+    #[test]
+    fn simple_nerf_dataset_compressed_members() {
+        let device = Default::default();
+
+        let arrays = [
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            (
+                "images",
+                npy_bytes(
+                    &[2, 5, 6, 3],
+                    (0..180).map(|index| index as f32 / 180.0).collect(),
+                ),
+            ),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ];
+        let [stored, deflated] =
+            [CompressionMethod::Stored, CompressionMethod::Deflated].map(
+                |method| {
+                    let dataset = SimpleNerfDatasetConfig::new(5, 2.0..6.0)
+                        .init_from_bytes::<Backend>(
+                        npz_bytes_with(&arrays, method),
+                        &device,
+                    );
+                    assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+                    dataset.unwrap()
+                },
+            );
+        assert_eq!(deflated.len(), 2);
+        assert_eq!(deflated.inners, stored.inners);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_directory_loading() {
+        let device = Default::default();
+
+        let directory = temp_directory("directory");
+        ZipArchive::new(File::open(TEST_DATA_FILE_PATH).unwrap())
+            .unwrap()
+            .extract(&directory)
+            .unwrap();
+
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_directory::<Backend>(&directory, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset_from_file = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        assert_eq!(
+            dataset.unwrap().inners.len(),
+            dataset_from_file.inners.len()
+        );
+
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_directory::<Backend>(directory.join("missing"), &device);
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::MissingArray { name, .. } if name == "images"
+        ));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_tar_gz_and_npy_files() {
+        let device = Default::default();
+
+        let arrays = [
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[2, 1, 2, 3], vec![0.5_f32; 12])),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ];
+        let dataset_from_npz = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(npz_bytes(&arrays), &device)
+            .unwrap();
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(tar_gz_bytes(&arrays), &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!(dataset.inners.len(), dataset_from_npz.inners.len());
+        assert_eq!(
+            dataset.inners[1].directions.value,
+            dataset_from_npz.inners[1].directions.value
+        );
+
+        let directory = temp_directory("npy-files");
+        for (name, bytes) in arrays.iter() {
+            std::fs::write(directory.join(format!("{}.npy", name)), bytes)
+                .unwrap();
+        }
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_npy_files::<Backend>(
+                directory.join("images.npy"),
+                directory.join("poses.npy"),
+                directory.join("focal.npy"),
+                &device,
+            );
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+        assert_eq!(
+            dataset.unwrap().inners[1].directions.value,
+            dataset_from_npz.inners[1].directions.value
+        );
+
+        let intrinsics = npy_bytes(
+            &[2, 3, 3],
+            [2.0_f32, 0.0, 0.75, 0.0, 2.0, 0.5, 0.0, 0.0, 1.0].repeat(2),
+        );
+        let mut arrays = arrays.to_vec();
+        arrays.push(("intrinsics", intrinsics.clone()));
+        let dataset_with_intrinsics = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(npz_bytes(&arrays), &device)
+            .unwrap();
+        std::fs::write(directory.join("intrinsics.npy"), intrinsics).unwrap();
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_npy_files::<Backend>(
+                directory.join("images.npy"),
+                directory.join("poses.npy"),
+                directory.join("focal.npy"),
+                &device,
+            );
+        std::fs::remove_file(directory.join("intrinsics.npy")).unwrap();
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+        assert_eq!(
+            dataset.unwrap().inners[1].directions.value,
+            dataset_with_intrinsics.inners[1].directions.value
+        );
+
+        // NOTE: The layout of the images is arranged like the other loaders,
+        // and the masks are read next to the images
+        std::fs::write(
+            directory.join("images_first.npy"),
+            npy_bytes(&[2, 3, 1, 2], vec![0.5_f32; 12]),
+        )
+        .unwrap();
+        std::fs::write(
+            directory.join("masks.npy"),
+            npy_bytes(&[2, 1, 2], vec![1.0_f32, 0.0, 1.0, 1.0]),
+        )
+        .unwrap();
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_image_layout(ImageLayout::ChannelsFirst)
+            .init_from_npy_files::<Backend>(
+                directory.join("images_first.npy"),
+                directory.join("poses.npy"),
+                directory.join("focal.npy"),
+                &device,
+            );
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!(
+            dataset.inners[1].directions.value,
+            dataset_from_npz.inners[1].directions.value
+        );
+        assert_eq!(
+            dataset.inners[0].mask.as_ref().map(|mask| mask.value.clone()),
+            Some(vec![1.0, 0.0])
+        );
+        std::fs::remove_file(directory.join("masks.npy")).unwrap();
+    }
+
+    #[test]
+    fn simple_nerf_dataset_rgba_images() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f64])),
+            (
+                "images",
+                npy_bytes(
+                    &[1, 1, 2, 4],
+                    vec![0.2_f32, 0.4, 0.6, 1.0, 0.2, 0.4, 0.6, 0.0],
+                ),
+            ),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_background(Some([0.0, 0.5, 1.0]))
+            .init_from_reader::<Backend, _>(io::Cursor::new(&archive), &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let inner = dataset.unwrap().inners.remove(0);
+        assert_eq!(inner.image.value, [0.2, 0.4, 0.6, 0.0, 0.5, 1.0]);
+        assert_eq!(inner.mask.unwrap().value, [1.0, 0.0]);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_background(None)
+            .init_from_reader::<Backend, _>(io::Cursor::new(&archive), &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let inner = dataset.unwrap().inners.remove(0);
+        assert_eq!(inner.image.value, [0.2, 0.4, 0.6, 0.2, 0.4, 0.6]);
+        assert_eq!(inner.mask.unwrap().value, [1.0, 0.0]);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_alpha_masks() {
+        let device = Default::default();
+
+        let alphas = [0.0_f32, 0.25, 0.5, 0.75, 1.0, 1.0, 0.5, 0.0, 0.25, 1.0];
+        let rgba = alphas
+            .iter()
+            .flat_map(|alpha| [0.2, 0.4, 0.6, *alpha])
+            .collect::<Vec<_>>();
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 2, 5, 4], rgba)),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let item = dataset.unwrap().get(0).unwrap();
+        assert_eq!(item.image.shape.dims, [2, 5, 3]);
+        let mask = item.mask.unwrap();
+        assert_eq!(mask.shape.dims, [2, 5, 1]);
+        assert_eq!(mask.value, alphas);
+        assert!(mask.value.iter().all(|value| (0.0..=1.0).contains(value)));
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 2, 5, 3], vec![0.5_f32; 30])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+        let item = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap()
+            .get(0)
+            .unwrap();
+        assert!(item.mask.is_none());
+    }
+
+    #[test]
+    fn simple_nerf_dataset_intrinsics() {
+        let device = Default::default();
+
+        let intrinsics = [
+            [2.0, 0.0, 1.0, 0.0, 2.0, 0.5, 0.0, 0.0, 1.0],
+            [4.0, 0.0, 0.5, 0.0, 3.0, 0.5, 0.0, 0.0, 1.0],
+        ]
+        .concat();
+        let archive = npz_bytes(&[
+            ("images", npy_bytes(&[2, 1, 2, 3], vec![0.0_f32; 12])),
+            ("intrinsics", npy_bytes(&[2, 3, 3], intrinsics.clone())),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_reader::<Backend, _>(io::Cursor::new(&archive), &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        let directions = &dataset.inners[0].directions;
+        assert_eq!(directions.value[0..3], [-0.5, 0.25, -1.0]);
+        assert_ne!(directions.value, dataset.inners[1].directions.value);
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[2, 1, 2, 3], vec![0.0_f32; 12])),
+            ("intrinsics", npy_bytes(&[3, 3], intrinsics[0..9].to_vec())),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_reader::<Backend, _>(io::Cursor::new(&archive), &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!(
+            dataset.inners[0].directions.value,
+            dataset.inners[1].directions.value
+        );
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![3.0_f32])),
+            ("images", npy_bytes(&[2, 1, 2, 3], vec![0.0_f32; 12])),
+            ("intrinsics", npy_bytes(&[2, 3, 3], intrinsics)),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_reader::<Backend, _>(io::Cursor::new(&archive), &device);
+        assert!(matches!(dataset.unwrap_err(), DatasetError::Parse(_)));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_disparity_sampling() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 1.0..8.0)
+            .with_sampling(DistanceSampling::Disparity)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let distances = &dataset.unwrap().inners[0].distances[0..4];
+        let widths = distances
+            .windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .collect::<Vec<_>>();
+        assert_eq!(distances[0], 1.0);
+        assert!(widths.iter().all(|width| *width > 0.0), "{:?}", distances);
+        assert!(widths.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 0.0..8.0)
+            .with_sampling(DistanceSampling::Disparity)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(matches!(dataset.unwrap_err(), DatasetError::InvalidConfig(_)));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_ndc_rays() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 2, 4, 3], vec![0.5_f32; 24])),
+            (
+                "poses",
+                npy_bytes(
+                    &[1, 3, 4],
+                    vec![
+                        1.0_f32, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0,
+                        1.0, 0.0,
+                    ],
+                ),
+            ),
+        ]);
+        let config = SimpleNerfDatasetConfig::new(4, 1.5..6.0).with_ndc(true);
+        let dataset = config.init_from_bytes::<Backend>(&archive, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let item = dataset.unwrap().get(0).unwrap();
+        assert_eq!(item.distances.value[0..4], [0.0, 0.25, 0.5, 0.75]);
+        for (position, distance) in item
+            .positions
+            .value
+            .chunks_exact(3)
+            .zip(item.distances.value.iter())
+        {
+            assert!(position.iter().all(|value| value.abs() <= 1.0 + 1e-5));
+            assert!((position[2] - (2.0 * distance - 1.0)).abs() < 1e-5);
+        }
+
+        let depths = config.ndc_to_depth(Tensor::<Backend, 1>::from_floats(
+            [0.0, 0.5, 0.75],
+            &device,
+        ));
+        depths.into_data().assert_approx_eq(&Data::from([1.5, 3.0, 6.0]), 5);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 0.0..6.0)
+            .with_ndc(true)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(matches!(dataset.unwrap_err(), DatasetError::InvalidConfig(_)));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_normalized_directions() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 2, 4, 3], vec![0.5_f32; 24])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+        let config = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_sampling_strategy(SamplingStrategy::Deterministic);
+        let item = config
+            .clone()
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap()
+            .get(0)
+            .unwrap();
+        let normalized = config
+            .with_normalize_directions(true)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(normalized.is_ok(), "Error: {}", normalized.unwrap_err());
+
+        // NOTE: The directions are normalized once while loading
+        let normalized = normalized.unwrap();
+        for direction in normalized.inners[0].directions.value.chunks_exact(3) {
+            let norm = direction.iter().map(|value| value * value).sum::<f32>();
+            assert!((norm - 1.0).abs() < 1e-5);
+        }
+
+        let normalized = normalized.get(0).unwrap();
+        normalized.positions.assert_approx_eq(&item.positions, 5);
+        for (direction, normalized_direction) in item
+            .directions
+            .value
+            .chunks_exact(3)
+            .zip(normalized.directions.value.chunks_exact(3))
+        {
+            let norm = direction.iter().map(|value| value * value).sum::<f32>();
+            let norm = norm.sqrt();
+            for (value, normalized_value) in
+                direction.iter().zip(normalized_direction)
+            {
+                assert!((value / norm - normalized_value).abs() < 1e-5);
+            }
+        }
+
+        // NOTE: The corner ray of (-1.0, 0.5, -1.0) has the length of 1.5,
+        // and the last interval stays unbounded
+        let norm = 1.5;
+        assert_eq!(item.intervals.value[0..4], [1.0, 1.0, 1.0, 1e9]);
+        for (interval, expected) in
+            normalized.intervals.value[0..3].iter().zip([norm; 3])
+        {
+            assert!((interval - expected).abs() < 1e-5);
+        }
+        assert!(normalized.intervals.value[3] >= 1e9);
+        assert!((normalized.distances.value[0] - 2.0 * norm).abs() < 1e-5);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_nonlinear_sampling() {
+        let device = Default::default();
+
+        for sampling in [
+            DistanceSampling::Linear,
+            DistanceSampling::Disparity,
+            DistanceSampling::Log,
+        ] {
+            let dataset = SimpleNerfDatasetConfig::new(8, 1.0..16.0)
+                .with_sampling(sampling)
+                .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+            assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+            let dataset = dataset.unwrap();
+            let inner = &dataset.inners[0];
+            assert_eq!(inner.distances[0], 1.0);
+            assert!(
+                (inner.distances[7] + inner.distance_widths[7] as f32 - 16.0)
+                    .abs()
+                    < 1e-5
+            );
+            assert!(inner.distances.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(inner.distance_widths.iter().all(|width| *width > 0.0));
+
+            // NOTE: The jitter stays in the nonuniform bins
+            let train = dataset.split_for_training(1.0).train;
+            let distances = train.get(0).unwrap().distances.value;
+            assert!(distances
+                .iter()
+                .all(|distance| (1.0..16.0).contains(distance)));
+            assert!(distances
+                .chunks_exact(8)
+                .all(|ray| ray.windows(2).all(|pair| pair[0] < pair[1])));
+        }
+
+        let dataset = SimpleNerfDatasetConfig::new(8, 1.0..16.0)
+            .with_sampling(DistanceSampling::Log)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        assert!((dataset.inners[0].distances[4] - 4.0).abs() < 1e-5);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 0.0..8.0)
+            .with_sampling(DistanceSampling::Log)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(matches!(dataset.unwrap_err(), DatasetError::InvalidConfig(_)));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_per_image_focals() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[2], vec![2.0_f32, 4.0])),
+            ("images", npy_bytes(&[2, 1, 2, 3], vec![0.0_f32; 12])),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_reader::<Backend, _>(io::Cursor::new(&archive), &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!(
+            dataset.inners[0].directions.value[0..3],
+            [-0.5, 0.25, -1.0]
+        );
+        assert_eq!(
+            dataset.inners[1].directions.value[0..3],
+            [-0.25, 0.125, -1.0]
+        );
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[3], vec![2.0_f32, 4.0, 8.0])),
+            ("images", npy_bytes(&[2, 1, 2, 3], vec![0.0_f32; 12])),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_reader::<Backend, _>(io::Cursor::new(&archive), &device);
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::ShapeMismatch { name, got, .. }
+                if name == "focal" && got == [3]
+        ));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_camera_conventions() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 2, 2, 3], vec![0.0_f32; 12])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+
+        let directions = [CameraConvention::OpenGl, CameraConvention::OpenCv]
+            .map(|convention| {
+                SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+                    .with_convention(convention)
+                    .init_from_bytes::<Backend>(&archive, &device)
+                    .unwrap()
+                    .inners
+                    .remove(0)
+                    .directions
+                    .value
+            });
+        assert_ne!(directions[0], directions[1]);
+        for (opengl, opencv) in
+            directions[0].chunks_exact(3).zip(directions[1].chunks_exact(3))
+        {
+            assert_eq!(opencv, [opengl[0], -opengl[1], -opengl[2]]);
+        }
+    }
+
+    #[test]
+    fn simple_nerf_dataset_downscaling() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            (
+                "images",
+                npy_bytes(
+                    &[1, 2, 4, 3],
+                    (0..24).map(|value| value as f32 / 24.0).collect(),
+                ),
+            ),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+
+        let original = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_image_layout(ImageLayout::ChannelsLast)
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap();
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_image_layout(ImageLayout::ChannelsLast)
+            .with_downscale(2)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!(dataset.height(), 1);
+        assert_eq!(dataset.width(), 2);
+        assert_eq!(dataset.focal(), 1.0);
+        assert_eq!(dataset.inners[0].image.shape.dims, [1, 2, 3]);
+        assert_eq!(dataset.inners[0].directions.shape.dims, [1, 2, 4, 3]);
+
+        // NOTE: The pooled pixels average the colors and directions of blocks
+        let image = &dataset.inners[0].image.value;
+        assert!((image[0] - 7.5 / 24.0).abs() < 1e-6);
+        assert!((image[3] - 13.5 / 24.0).abs() < 1e-6);
+        let directions = avg_pool2d(
+            Tensor::<Backend, 4>::from_data(
+                original.inners[0].directions.clone().convert(),
+                &device,
+            )
+            .slice([0..2, 0..4])
+            .swap_dims(0, 3)
+            .swap_dims(1, 2),
+            [2, 2],
+            [2, 2],
+            [0, 0],
+            false,
+        )
+        .swap_dims(1, 2)
+        .swap_dims(0, 3);
+        directions
+            .into_data()
+            .convert::<f32>()
+            .assert_approx_eq(&dataset.inners[0].directions, 5);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_image_layout(ImageLayout::ChannelsLast)
+            .with_downscale(4)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(matches!(dataset.unwrap_err(), DatasetError::InvalidConfig(_)));
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 3, 5, 3], vec![0.5_f32; 45])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_image_layout(ImageLayout::ChannelsLast)
+            .with_downscale(2)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(matches!(dataset.unwrap_err(), DatasetError::InvalidConfig(_)));
+
+        let original = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_downscale(2)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        assert_eq!(dataset.image_size(), (50, 50));
+        assert_eq!(dataset.get(0).unwrap().image.shape.dims, [50, 50, 3]);
+        assert_eq!(dataset.focal(), original.focal() / 2.0);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_cropping() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            (
+                "images",
+                npy_bytes(
+                    &[1, 4, 6, 3],
+                    (0..72).map(|value| value as f32 / 72.0).collect(),
+                ),
+            ),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+
+        let original = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_image_layout(ImageLayout::ChannelsLast)
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap();
+        let original_directions = Tensor::<Backend, 4>::from_data(
+            original.inners[0].directions.clone().convert(),
+            &device,
+        );
+        let original_image = Tensor::<Backend, 3>::from_data(
+            original.inners[0].image.clone().convert(),
+            &device,
+        );
+
+        // NOTE: Both crops keep the pixels in rows 1..3 and columns 1..4
+        for crop in [ImageCrop::Center(0.5), ImageCrop::Region([1, 1, 3, 2])] {
+            let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+                .with_image_layout(ImageLayout::ChannelsLast)
+                .with_crop(Some(crop))
+                .init_from_bytes::<Backend>(&archive, &device);
+            assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+            let dataset = dataset.unwrap();
+            assert_eq!(dataset.height(), 2);
+            assert_eq!(dataset.width(), 3);
+            assert_eq!(
+                dataset.inners[0].directions.value,
+                original_directions
+                    .clone()
+                    .slice([1..3, 1..4])
+                    .into_data()
+                    .value
+            );
+            assert_eq!(
+                dataset.inners[0].image.value,
+                original_image.clone().slice([1..3, 1..4]).into_data().value
+            );
+        }
+
+        for crop in [ImageCrop::Center(0.0), ImageCrop::Region([4, 0, 3, 2])] {
+            let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+                .with_image_layout(ImageLayout::ChannelsLast)
+                .with_crop(Some(crop))
+                .init_from_bytes::<Backend>(&archive, &device);
+            assert!(matches!(
+                dataset.unwrap_err(),
+                DatasetError::InvalidConfig(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn simple_nerf_dataset_pose_layouts() {
+        let device = Default::default();
+
+        let archive = |shape: &[u64], poses: Vec<f32>| {
+            npz_bytes(&[
+                ("focal", npy_bytes(&[], vec![2.0_f32])),
+                ("images", npy_bytes(&[1, 1, 2, 3], vec![0.0_f32; 6])),
+                ("poses", npy_bytes(shape, poses)),
+            ])
+        };
+        let homogeneous =
+            [identity_poses(1), vec![0.0, 0.0, 0.0, 1.0]].concat();
+
+        for shape in [&[1, 3, 4][..], &[1, 4, 4], &[1, 16]] {
+            let poses =
+                homogeneous[0..shape.iter().product::<u64>() as usize].to_vec();
+            let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+                .init_from_bytes::<Backend>(archive(shape, poses), &device);
+            assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+            assert_eq!(dataset.unwrap().inners[0].pose, identity_poses(1));
+        }
+
+        let mut poses = homogeneous.clone();
+        poses[14] = 1.0;
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(archive(&[1, 4, 4], poses), &device);
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::InvalidPose {
+                index: 0,
+                ..
+            }
+        ));
+
+        let error = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(
+                archive(&[1, 3, 3], vec![0.0; 9]),
+                &device,
+            )
+            .unwrap_err();
+        assert!(error.to_string().contains("[1, 3, 3]"), "{}", error);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(
+                archive(&[1, 2, 4], vec![0.0; 8]),
+                &device,
+            );
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::ShapeMismatch { name, got, .. }
+                if name == "poses" && got == [1, 2, 4]
+        ));
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_tensors::<Backend>(
+                Tensor::zeros([1, 1, 2, 3], &device),
+                Tensor::zeros([1, 2, 4], &device),
+                2.0,
+                &device,
+            );
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::ShapeMismatch { name, got, .. }
+                if name == "poses" && got == [1, 2, 4]
+        ));
+
+        // NOTE: The world-to-camera rotation is 90 degrees about z
+        let poses =
+            vec![0.0, -1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 2.0, 0.0, 0.0, 1.0, 3.0];
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_invert_poses(true)
+            .init_from_bytes::<Backend>(archive(&[1, 3, 4], poses), &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+        assert_eq!(
+            dataset.unwrap().inners[0].pose,
+            [0.0, 1.0, 0.0, -2.0, -1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, -3.0]
+        );
+    }
+
+    #[test]
+    fn simple_nerf_dataset_pose_validation() {
+        let device = Default::default();
+
+        let archive = |poses: Vec<f32>| {
+            npz_bytes(&[
+                ("focal", npy_bytes(&[], vec![2.0_f32])),
+                ("images", npy_bytes(&[2, 1, 2, 3], vec![0.0_f32; 12])),
+                ("poses", npy_bytes(&[2, 3, 4], poses)),
+            ])
+        };
+
+        let mut poses = identity_poses(2);
+        poses[12] = 1.01;
+        poses[13] = 0.02;
+        let drifted = archive(poses);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&drifted, &device);
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::InvalidPose {
+                index: 1,
+                ..
+            }
+        ));
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_orthonormalize_poses(true)
+            .init_from_bytes::<Backend>(&drifted, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let pose = &dataset.unwrap().inners[1].pose;
+        let axes = [0, 1, 2]
+            .map(|column| [pose[column], pose[4 + column], pose[8 + column]]);
+        for row in 0..3 {
+            for column in 0..3 {
+                let product = (0..3)
+                    .map(|index| axes[row][index] * axes[column][index])
+                    .sum::<f32>();
+                let identity = if row == column {
+                    1.0
+                } else {
+                    0.0
+                };
+                assert!((product - identity).abs() < 1e-6);
+            }
+        }
+        assert_eq!([pose[3], pose[7], pose[11]], [0.0, 0.0, 4.0]);
+
+        let mut poses = identity_poses(2);
+        poses[3] = f32::NAN;
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_orthonormalize_poses(true)
+            .init_from_bytes::<Backend>(archive(poses), &device);
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::InvalidPose {
+                index: 0,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_concatenation() {
+        let device = Default::default();
+
+        let config = SimpleNerfDatasetConfig::new(7, 2.0..6.0);
+        let dataset = config
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let dataset_size = dataset.len();
+
+        let concatenated = dataset.clone().concat(dataset.clone());
+        assert!(concatenated.is_ok(), "Error: {}", concatenated.unwrap_err());
+
+        let concatenated = concatenated.unwrap();
+        assert_eq!(concatenated.len(), dataset_size * 2);
+        assert_eq!(
+            concatenated.get(dataset_size).unwrap().image,
+            dataset.get(0).unwrap().image
+        );
+
+        let datasets = dataset.clone().split_for_training(0.5);
+        let concatenated =
+            datasets.train.clone().concat(datasets.train.clone()).unwrap();
+        assert!(concatenated.has_noisy_distance());
+        let concatenated = datasets.train.concat(datasets.test);
+        assert!(concatenated.is_ok(), "Error: {}", concatenated.unwrap_err());
+
+        let concatenated = concatenated.unwrap();
+        assert_eq!(concatenated.len(), dataset_size);
+        assert!(!concatenated.has_noisy_distance());
+
+        let downscaled = config
+            .clone()
+            .with_downscale(2)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        assert!(matches!(
+            dataset.clone().concat(downscaled).unwrap_err(),
+            DatasetError::ShapeMismatch { .. }
+        ));
+
+        let denser = SimpleNerfDatasetConfig::new(8, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        assert!(matches!(
+            dataset.clone().concat(denser).unwrap_err(),
+            DatasetError::ShapeMismatch { .. }
+        ));
+
+        let disparate = config
+            .clone()
+            .with_sampling(DistanceSampling::Disparity)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        assert!(matches!(
+            dataset.concat(disparate).unwrap_err(),
+            DatasetError::InvalidConfig(_)
+        ));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_tensor_loading() {
+        let device = Default::default();
+
+        let images = Tensor::<Backend, 4>::random(
+            [2, 3, 4, 3],
+            Distribution::Default,
+            &device,
+        );
+        let poses = Tensor::<Backend, 1>::from_floats(
+            identity_poses(2).as_slice(),
+            &device,
+        )
+        .reshape([2, 3, 4]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_tensors(images.clone(), poses.clone(), 2.0, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            (
+                "images",
+                npy_bytes(&[2, 3, 4, 3], images.clone().into_data().value),
+            ),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+        let expected = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_image_layout(ImageLayout::ChannelsLast)
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap();
+        let dataset = dataset.unwrap();
+        assert_eq!(dataset.len(), 2);
+        for (inner, expected_inner) in
+            dataset.inners.iter().zip(&expected.inners)
+        {
+            assert_eq!(inner.directions, expected_inner.directions);
+            assert_eq!(inner.image, expected_inner.image);
+            assert_eq!(inner.origins, expected_inner.origins);
+        }
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_tensors(
+                images.clone(),
+                poses.clone().slice([0..1, 0..3, 0..4]),
+                2.0,
+                &device,
+            );
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::ShapeMismatch { .. }
+        ));
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_tensors(
+                images.slice([0..2, 0..3, 0..4, 0..2]),
+                poses,
+                2.0,
+                &device,
+            );
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::ShapeMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_parse_modes() {
+        let device = Default::default();
+
+        let mut poses = identity_poses(3);
+        poses[12..24].copy_from_slice(&[0.0; 12]);
+        let mut images = npy_bytes(&[3, 1, 2, 3], vec![0.5_f32; 18]);
+        images.truncate(images.len() - 4 * 6);
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", images),
+            ("poses", npy_bytes(&[3, 3, 4], poses.clone())),
+        ]);
+
+        // NOTE: The images are read before the poses are checked,
+        // so the strict mode fails at the truncated image
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::Io(error)
+                if error.kind() == io::ErrorKind::UnexpectedEof
+        ));
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(
+                npz_bytes(&[
+                    ("focal", npy_bytes(&[], vec![2.0_f32])),
+                    ("images", npy_bytes(&[3, 1, 2, 3], vec![0.5_f32; 18])),
+                    ("poses", npy_bytes(&[3, 3, 4], poses)),
+                ]),
+                &device,
+            );
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::InvalidPose {
+                index: 1,
+                ..
+            }
+        ));
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_parse_mode(ParseMode::Lenient)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!(dataset.len(), 1);
+        assert_eq!(
+            dataset
+                .warnings()
+                .iter()
+                .map(|warning| warning.index)
+                .collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert_eq!(dataset.inners[0].pose, identity_poses(1));
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 1, 2, 3], vec![0.5_f32; 6])),
+            ("poses", npy_bytes(&[1, 3, 4], vec![0.0_f32; 12])),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_parse_mode(ParseMode::Lenient)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(matches!(dataset.unwrap_err(), DatasetError::Parse(_)));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_image_subset() {
+        let device = Default::default();
+
+        let config = SimpleNerfDatasetConfig::new(4, 2.0..6.0);
+        let dataset = config
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+
+        let subset =
+            config
+                .clone()
+                .with_image_indices(Some(vec![5, 1, 3, 7]))
+                .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(subset.is_ok(), "Error: {}", subset.unwrap_err());
+
+        let subset = subset.unwrap();
+        assert_eq!(subset.len(), 4);
+        for (index, source_index) in [5, 1, 3, 7].iter().copied().enumerate() {
+            assert_eq!(
+                subset.inners[index].image,
+                dataset.inners[source_index].image
+            );
+            assert_eq!(
+                subset.inners[index].pose,
+                dataset.inners[source_index].pose
+            );
+        }
+
+        let datasets = subset.split_for_training(0.5);
+        assert_eq!(datasets.train.len(), 2);
+        assert_eq!(datasets.test.len(), 2);
+
+        let error = config
+            .with_image_indices(Some(vec![0, 106]))
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap_err();
+        assert!(error.to_string().contains("0..106"), "{}", error);
+        assert!(matches!(error, DatasetError::InvalidConfig(_)));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_resizing() {
+        let device = Default::default();
+
+        let config = SimpleNerfDatasetConfig::new(4, 2.0..6.0);
+        let dataset = config
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let resized = config
+            .clone()
+            .with_resize_to(Some((50, 40)))
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(resized.is_ok(), "Error: {}", resized.unwrap_err());
+
+        let resized = resized.unwrap();
+        assert_eq!(resized.len(), dataset.len());
+        assert_eq!((resized.width(), resized.height()), (50, 40));
+        assert_eq!(resized.inners[0].image.shape.dims, [40, 50, 3]);
+        let [focal_x, focal_y, center_x, center_y] =
+            resized.inners[0].intrinsics;
+        assert!((focal_x - dataset.focal() * 0.5).abs() < 1e-4);
+        assert!((focal_y - dataset.focal() * 0.4).abs() < 1e-4);
+        assert_eq!([center_x, center_y], [25.0, 20.0]);
+
+        let resized = config
+            .with_resize_to(Some((0, 40)))
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(matches!(resized.unwrap_err(), DatasetError::InvalidConfig(_)));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_object_arrays() {
+        let device = Default::default();
+
+        // NOTE: The header of a pickled object array saved by numpy
+        let header =
+            "{'descr': '|O', 'fortran_order': False, 'shape': (2,), }\n";
+        let mut images = b"\x93NUMPY\x01\x00".to_vec();
+        images.extend((header.len() as u16).to_le_bytes());
+        images.extend(header.bytes());
+        images.extend([0x80, 0x02]);
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", images),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+        let error = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap_err();
+        assert!(error.to_string().contains("share"), "{}", error);
+        assert!(matches!(error, DatasetError::Parse(_)));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_depths() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("depths", npy_bytes(&[2, 1, 2], vec![0.0_f32, 4.0, 3.0, 5.0])),
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[2, 1, 2, 3], vec![0.5_f32; 12])),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let input =
+            dataset.unwrap().get(1).unwrap().into_input::<Backend>(&device);
+        let depth = input.depth.clone().unwrap();
+        assert_eq!(depth.dims(), [1, 2, 1]);
+        assert_eq!(depth.device(), device);
+        assert_eq!(depth.into_data().value, [3.0, 5.0]);
+        assert_eq!(input.sample_rays(2, 0).depth.unwrap().dims(), [2, 1, 1]);
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[2, 1, 2, 3], vec![0.5_f32; 12])),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap();
+        assert!(dataset.get(0).unwrap().depth.is_none());
+
+        let archive = npz_bytes(&[
+            ("depths", npy_bytes(&[2, 2, 1], vec![0.0_f32; 4])),
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[2, 1, 2, 3], vec![0.5_f32; 12])),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::ShapeMismatch { name, .. } if name == "depths"
+        ));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_mask_arrays() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[3, 1, 2, 3], vec![0.5_f32; 18])),
+            ("masks", npy_bytes(&[3, 1, 2], vec![255_u8, 0, 255, 255, 0, 0])),
+            ("poses", npy_bytes(&[3, 3, 4], identity_poses(3))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!(dataset.channel_count(), 3);
+        let input = dataset.get(0).unwrap().into_input::<Backend>(&device);
+        let mask = input.mask.unwrap();
+        assert_eq!(mask.dims(), [1, 2, 1]);
+        assert_eq!(mask.into_data().value, [1.0, 0.0]);
+
+        let split = dataset.clone().split_by_indices(&[1]);
+        assert_eq!(split.train.get(0).unwrap().mask.unwrap().value, [1.0, 1.0]);
+        assert_eq!(split.test.get(1).unwrap().mask.unwrap().value, [0.0, 0.0]);
+
+        let filtered = dataset.filter_by_mask_coverage(0.5);
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered.get(1).unwrap().mask.unwrap().value, [1.0, 1.0]);
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[3, 1, 2, 3], vec![0.5_f32; 18])),
+            ("masks", npy_bytes(&[3, 2, 1], vec![1.0_f32; 6])),
+            ("poses", npy_bytes(&[3, 3, 4], identity_poses(3))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::ShapeMismatch { name, .. } if name == "masks"
+        ));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_device_moving() {
+        let device = burn::backend::wgpu::WgpuDevice::BestAvailable;
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[2, 1, 2, 3], vec![0.5_f32; 12])),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &Default::default())
+            .unwrap()
+            .to_device(&device);
+        assert_eq!(dataset.device(), &device);
+
+        let input =
+            dataset.get(0).unwrap().into_input::<Backend>(dataset.device());
+        assert_eq!(input.directions.device(), device);
+        assert_eq!(input.distances.device(), device);
+        assert_eq!(dataset.pose_tensor(0).unwrap().device(), device);
+
+        let split = dataset.split_for_training(0.5);
+        assert_eq!(split.train.device(), &device);
+        assert_eq!(split.test.device(), &device);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_bounds() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("bounds", npy_bytes(&[2, 2], vec![1.0_f32, 2.0, 4.0, 8.0])),
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[2, 1, 2, 3], vec![0.5_f32; 12])),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        let distances =
+            dataset.iter().map(|data| data.distances.value).collect::<Vec<_>>();
+        assert_ne!(distances[0], distances[1]);
+        assert_eq!(distances[0][0..4], [1.0, 1.25, 1.5, 1.75]);
+        assert_eq!(distances[1][0..4], [4.0, 5.0, 6.0, 7.0]);
+
+        let archive = npz_bytes(&[
+            ("bounds", npy_bytes(&[2, 3], vec![1.0_f32; 6])),
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[2, 1, 2, 3], vec![0.5_f32; 12])),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::ShapeMismatch { name, .. } if name == "bounds"
+        ));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_image_transform() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let transformed = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_image_transform(|image: Tensor<Backend, 3>| image / 2.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(transformed.is_ok(), "Error: {}", transformed.unwrap_err());
+
+        let transformed = transformed.unwrap();
+        assert_eq!(transformed.channel_count(), 3);
+        for index in [0, dataset.len() - 1] {
+            let image = &dataset.inners[index].image;
+            let halved = &transformed.inners[index].image;
+            assert_eq!(halved.shape, image.shape);
+            for (halved, value) in halved.value.iter().zip(&image.value) {
+                assert_eq!(*halved, value / 2.0);
+            }
+            assert_eq!(
+                transformed.inners[index].directions,
+                dataset.inners[index].directions
+            );
+        }
+
+        let result = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_image_transform(|image: Tensor<Backend, 3>| {
+                let [height, width, _] = image.dims();
+                image.slice([0..height, 0..width, 0..1])
+            })
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(matches!(result.unwrap_err(), DatasetError::InvalidConfig(_)));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_near_far_bounds() {
+        let device = Default::default();
+
+        let archive = |near_far: Vec<f32>| {
+            npz_bytes(&[
+                ("focal", npy_bytes(&[], vec![2.0_f32])),
+                ("images", npy_bytes(&[2, 1, 2, 3], vec![0.5_f32; 12])),
+                ("near_far", npy_bytes(&[2, 2], near_far)),
+                ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+            ])
+        };
+
+        let bounds = [[1.0, 3.0], [2.5, 10.5]];
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(archive(bounds.concat()), &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        for (index, &[near, far]) in bounds.iter().enumerate() {
+            let distances = dataset.get(index).unwrap().distances.value;
+            let inner = &dataset.inners[index];
+            assert_eq!(distances[0], near);
+            assert_eq!(distances[3] + inner.distance_widths[3] as f32, far);
+        }
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(archive(vec![1.0; 4]), &device);
+        assert!(matches!(dataset.unwrap_err(), DatasetError::InvalidConfig(_)));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_channel_first_images() {
+        let device = Default::default();
+
+        let archive = |shape: &[u64], images: Vec<f32>| {
+            npz_bytes(&[
+                ("focal", npy_bytes(&[], vec![2.0_f32])),
+                ("images", npy_bytes(shape, images)),
+                ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+            ])
+        };
+
+        let planar = archive(&[1, 3, 1, 2], vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&planar, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!((dataset.width(), dataset.height()), (2, 1));
+        assert_eq!(
+            dataset.inners[0].image.value,
+            [0.1, 0.3, 0.5, 0.2, 0.4, 0.6]
+        );
+
+        let square = archive(&[1, 3, 3, 3], vec![0.5; 27]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&square, &device);
+        assert!(matches!(dataset.unwrap_err(), DatasetError::InvalidConfig(_)));
+
+        for layout in [ImageLayout::ChannelsFirst, ImageLayout::ChannelsLast] {
+            let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+                .with_image_layout(layout)
+                .init_from_bytes::<Backend>(&square, &device);
+            assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+            assert_eq!(dataset.unwrap().inners[0].image.shape.dims, [3, 3, 3]);
+        }
+    }
+
+    #[test]
+    fn simple_nerf_dataset_caching() {
+        let device = Default::default();
+
+        let split = SimpleNerfDatasetConfig::new(8, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap()
+            .split_for_training(0.9);
+        let train = split.train.cached();
+        assert!(!train.is_cached());
+
+        let test = split.test;
+        let cached = test.clone().cached();
+        assert!(cached.is_cached());
+        assert_eq!(cached.len(), test.len());
+
+        let items = (0..test.len())
+            .map(|index| test.get(index).unwrap())
+            .collect::<Vec<_>>();
+        let cached_items = (0..cached.len())
+            .map(|index| cached.get(index).unwrap())
+            .collect::<Vec<_>>();
+
+        for (item, cached_item) in items.iter().zip(&cached_items) {
+            assert_eq!(item.directions.shape, cached_item.directions.shape);
+            assert_eq!(item.positions.shape, cached_item.positions.shape);
+            assert_eq!(item.positions, cached_item.positions);
+            assert_eq!(item.intervals, cached_item.intervals);
+        }
+        assert_eq!(
+            cached.get(0).unwrap().positions,
+            cached.get(0).unwrap().positions
+        );
+        assert!(cached.get(cached.len()).is_none());
+
+        let mut shuffled = cached;
+        shuffled.shuffle(0);
+        assert!(!shuffled.is_cached());
+    }
+
+    #[test]
+    fn simple_nerf_dataset_compact_distances() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(5, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let inner = &dataset.inners[0];
+        assert_eq!(inner.distances, [2.0, 2.8, 3.6, 4.4, 5.2]);
+
+        let item = dataset.get(0).unwrap();
+        assert_eq!(item.distances.shape.dims, [100, 100, 5, 1]);
+        assert_eq!(item.distances.value[0..5], inner.distances);
+        assert_eq!(item.distances.value[49995..50000], inner.distances);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_iteration() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+
+        let mut items = dataset.iter();
+        assert_eq!(items.len(), dataset.len());
+        items.next();
+        assert_eq!(items.len(), dataset.len() - 1);
+
+        let items = dataset.iter().collect::<Vec<_>>();
+        assert_eq!(items.len(), dataset.len());
+        for (index, item) in (&dataset).into_iter().enumerate() {
+            let expected = dataset.get(index).unwrap();
+            assert_eq!(item.directions.shape, expected.directions.shape);
+            assert_eq!(item.distances.shape, expected.distances.shape);
+            assert_eq!(item.image.shape, expected.image.shape);
+            assert_eq!(item.intervals.shape, expected.intervals.shape);
+            assert_eq!(item.positions.shape, expected.positions.shape);
+        }
+    }
+
+    #[test]
+    fn simple_nerf_dataset_parallel_loading() {
+        let device = Default::default();
+
+        let serial = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(serial.is_ok(), "Error: {}", serial.unwrap_err());
+
+        let parallel = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_parallel_load(true)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(parallel.is_ok(), "Error: {}", parallel.unwrap_err());
+
+        let serial = serial.unwrap();
+        let parallel = parallel.unwrap();
+        assert_eq!(parallel.len(), serial.len());
+        assert!(parallel.inners == serial.inners);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_per_image_rays() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let focal = dataset.focal();
+
+        for index in [0, 5, 105] {
+            let inner = &dataset.inners[index];
+            let pose = &inner.pose;
+            for (x, y) in [(0, 0), (10, 20), (99, 42)] {
+                let plane = [
+                    (x as f32 - 50.0) / focal,
+                    (50.0 - y as f32) / focal,
+                    -1.0,
+                ];
+                let offset = ((y * 100 + x) * 7) * 3;
+                for row in 0..3 {
+                    let expected = (0..3)
+                        .map(|column| pose[row * 4 + column] * plane[column])
+                        .sum::<f32>();
+                    let direction = inner.directions.value[offset + row];
+                    assert!(
+                        (direction - expected).abs() < 1e-5,
+                        "Direction {} of pixel ({}, {}) in image {}: {} != {}",
+                        row,
+                        x,
+                        y,
+                        index,
+                        direction,
+                        expected
+                    );
+                    let origin = inner.origins.value[(y * 100 + x) * 3 + row];
+                    assert_eq!(origin, pose[row * 4 + 3]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn simple_nerf_dataset_distortion() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 1, 2, 3], vec![0.0_f32; 6])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_distortion(Some([0.0; 4]))
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap();
+        assert_eq!(
+            dataset.inners[0].directions.value[0..3],
+            [-0.5, 0.25, -1.0]
+        );
+
+        // NOTE: The corner ray is distorted back to its pixel coordinates
+        let [k1, k2, p1, p2] = [0.1, 0.01, 0.001, 0.002];
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_distortion(Some([k1, k2, p1, p2]))
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap();
+        let direction = &dataset.inners[0].directions.value[0..3];
+        let (x, y) = (direction[0], -direction[1]);
+        let r2 = x * x + y * y;
+        let radial = 1.0 + k1 * r2 + k2 * r2 * r2;
+        let distorted_x =
+            x * radial + 2.0 * p1 * x * y + p2 * (r2 + 2.0 * x * x);
+        let distorted_y =
+            y * radial + p1 * (r2 + 2.0 * y * y) + 2.0 * p2 * x * y;
+        assert!((distorted_x - -0.5).abs() < 1e-5, "{}", distorted_x);
+        assert!((distorted_y - -0.25).abs() < 1e-5, "{}", distorted_y);
+        assert!(direction[0] > -0.5 && direction[1] < 0.25);
+        assert_eq!(direction[2], -1.0);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_uint8_images() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f64])),
+            ("images", npy_bytes(&[1, 1, 2, 3], vec![255_u8, 0, 51, 0, 0, 0])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_reader::<Backend, _>(io::Cursor::new(&archive), &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let inner = dataset.unwrap().inners.remove(0);
+        assert_eq!(inner.image.value, [1.0, 0.0, 0.2, 0.0, 0.0, 0.0]);
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f64])),
+            ("images", npy_bytes(&[1, 1, 2, 3], vec![255_i32, 0, 51, 0, 0, 0])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_reader::<Backend, _>(io::Cursor::new(&archive), &device);
+        assert!(matches!(dataset.unwrap_err(), DatasetError::Parse(_)));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_errors() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 1, 2, 3], vec![0.0_f32; 6])),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_reader::<Backend, _>(io::Cursor::new(&archive), &device);
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::MissingArray { name, available }
+                if name == "poses" && available == ["focal", "images"]
+        ));
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 2, 3], vec![0.0_f32; 6])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_reader::<Backend, _>(io::Cursor::new(&archive), &device);
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::ShapeMismatch { got, .. } if got == [1, 2, 3]
+        ));
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..2.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        let error = io::Error::from(dataset.unwrap_err());
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_invalid_configs() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 1, 2, 3], vec![0.0_f32; 6])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+        let configs = [
+            SimpleNerfDatasetConfig::new(4, 6.0..2.0),
+            SimpleNerfDatasetConfig::new(4, 2.0..2.0),
+            SimpleNerfDatasetConfig::new(4, -1.0..6.0),
+            SimpleNerfDatasetConfig::new(4, 2.0..f64::NAN),
+            SimpleNerfDatasetConfig::new(0, 2.0..6.0),
+        ];
+        for config in configs {
+            let dataset = config.init_from_bytes::<Backend>(&archive, &device);
+            assert!(
+                matches!(dataset, Err(DatasetError::InvalidConfig(_))),
+                "Config: {}",
+                config
+            );
+        }
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 0.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+    }
+
+    #[test]
+    fn simple_nerf_dataset_array_names() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal_length", npy_bytes(&[], vec![2.0_f32])),
+            ("rgb", npy_bytes(&[1, 1, 2, 3], vec![0.5_f32; 6])),
+            ("camera_to_world", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_array_names(
+                ArrayNames::new()
+                    .with_focal("focal_length".into())
+                    .with_images("rgb".into())
+                    .with_poses("camera_to_world".into()),
+            )
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+        assert_eq!(dataset.unwrap().focal(), 2.0);
+
+        let error = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .with_array_names(
+                ArrayNames::new()
+                    .with_focal("focal_length".into())
+                    .with_images("rgb".into()),
+            )
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap_err();
+        assert!(error.to_string().contains("camera_to_world"), "{}", error);
+        assert!(matches!(
+            error,
+            DatasetError::MissingArray { name, available }
+                if name == "poses"
+                    && available == ["camera_to_world", "focal_length", "rgb"]
+        ));
+
+        let error = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            DatasetError::MissingArray { name, .. } if name == "images"
+        ));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_camera_angle() {
+        let device = Default::default();
+
+        let angle = 2.0 * (0.25_f32).atan();
+        let archive = npz_bytes(&[
+            ("camera_angle_x", npy_bytes(&[], vec![angle as f64])),
+            ("images", npy_bytes(&[1, 1, 2, 3], vec![0.5_f32; 6])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+        assert!((dataset.unwrap().focal() - 4.0).abs() < 1e-4);
+
+        let archive = npz_bytes(&[
+            ("camera_angle_x", npy_bytes(&[], vec![angle as f64])),
+            ("focal", npy_bytes(&[], vec![3.0_f32])),
+            ("images", npy_bytes(&[1, 1, 2, 3], vec![0.5_f32; 6])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+        assert_eq!(dataset.unwrap().focal(), 3.0);
+
+        let archive = npz_bytes(&[
+            ("images", npy_bytes(&[1, 1, 2, 3], vec![0.5_f32; 6])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+        let error = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("focal"), "{}", message);
+        assert!(message.contains("camera_angle_x"), "{}", message);
+
+        let archive = npz_bytes(&[
+            ("camera_angle_x", npy_bytes(&[], vec![0.0_f32])),
+            ("images", npy_bytes(&[1, 1, 2, 3], vec![0.5_f32; 6])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device);
+        assert!(matches!(dataset.unwrap_err(), DatasetError::Parse(_)));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_float64_arrays() {
+        let device = Default::default();
+
+        let images = vec![0.25_f32, 0.5, 0.75, 1.0, 0.0, 0.125];
+        let poses = identity_poses(1);
+        let archive_f32 = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 1, 2, 3], images.clone())),
+            ("poses", npy_bytes(&[1, 3, 4], poses.clone())),
+        ]);
+        let archive_f64 = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f64])),
+            (
+                "images",
+                npy_bytes(
+                    &[1, 1, 2, 3],
+                    images.into_iter().map(f64::from).collect(),
+                ),
+            ),
+            (
+                "poses",
+                npy_bytes(
+                    &[1, 3, 4],
+                    poses.into_iter().map(f64::from).collect(),
+                ),
+            ),
+        ]);
+
+        let config = SimpleNerfDatasetConfig::new(4, 2.0..6.0);
+        let inners = [archive_f32, archive_f64].map(|archive| {
+            let dataset = config.init_from_reader::<Backend, _>(
+                io::Cursor::new(&archive),
+                &device,
+            );
+            assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+            dataset.unwrap().inners.remove(0)
+        });
+        assert_eq!(inners[0].directions.value, inners[1].directions.value);
+        assert_eq!(inners[0].image.value, inners[1].image.value);
+        assert_eq!(inners[0].origins.value, inners[1].origins.value);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_seeded_sampling() {
+        let device = Default::default();
+
+        let datasets = [Some(7), Some(7), Some(8)].map(|seed| {
+            SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+                .with_seed(seed)
+                .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+                .unwrap()
+                .split_for_training(1.0)
+                .train
+        });
+
+        let positions =
+            datasets.map(|dataset| dataset.get(0).unwrap().positions.value);
+        assert_eq!(positions[0], positions[1]);
+        assert_ne!(positions[0], positions[2]);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_epoch_seeded_sampling() {
+        let device = Default::default();
+
+        let [mut dataset, other] = [(); 2].map(|_| {
+            SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+                .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+                .unwrap()
+                .with_seed(7)
+                .split_for_training(1.0)
+                .train
+        });
+
+        let distances = dataset.get(0).unwrap().distances.value;
+        assert_eq!(distances, other.get(0).unwrap().distances.value);
+        assert_eq!(distances, dataset.get(0).unwrap().distances.value);
+
+        dataset.set_epoch(1);
+        assert_ne!(distances, dataset.get(0).unwrap().distances.value);
+        dataset.set_epoch(0);
+        assert_eq!(distances, dataset.get(0).unwrap().distances.value);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_quiet_logging() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[2, 1, 2, 3], vec![0.5_f32; 12])),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+        let config = SimpleNerfDatasetConfig::new(4, 2.0..6.0);
+
+        let dataset = config
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap()
+            .split_for_training(1.0)
+            .train;
+        assert!(dataset.sampling_message(0).is_none());
+
+        let dataset = config
+            .with_debug_sampling(true)
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap()
+            .split_for_training(1.0)
+            .train;
+        let message = dataset.sampling_message(1);
+        assert!(message.is_some());
+        assert!(message.unwrap().starts_with("Sampling intervals of item 1: "));
+        assert!(dataset.sampling_message(dataset.len()).is_none());
+    }
+
+    #[test]
+    fn simple_nerf_dataset_stratified_jitter() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(16, 1.0..8.0)
+            .with_sampling(DistanceSampling::Disparity)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap()
+            .split_for_training(1.0)
+            .train;
+
+        for index in 0..4 {
+            let intervals = dataset.get(index).unwrap().intervals.value;
+            assert!(intervals.iter().all(|interval| *interval >= 0.0));
+        }
+    }
+
+    #[test]
+    fn simple_nerf_dataset_sampling_strategies() {
+        let device = Default::default();
+
+        let config =
+            SimpleNerfDatasetConfig::new(16, 2.0..6.0).with_seed(Some(7));
+        let dataset = config
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap()
+            .split_for_training(1.0)
+            .train;
+        for index in 0..4 {
+            let distances = dataset.get(index).unwrap().distances.value;
+            assert!(distances
+                .iter()
+                .all(|distance| (2.0..6.0).contains(distance)));
+            assert!(distances
+                .chunks_exact(16)
+                .all(|ray| ray.windows(2).all(|pair| pair[0] < pair[1])));
+        }
+
+        let dataset = config
+            .with_sampling_strategy(SamplingStrategy::Deterministic)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let starts = (0..16).map(|index| 2.0 + index as f32 * 0.25);
+        let distances = dataset
+            .clone()
+            .split_for_training(1.0)
+            .train
+            .get(0)
+            .unwrap()
+            .distances
+            .value;
+        assert_eq!(distances[0..16], starts.collect::<Vec<_>>());
+        assert_eq!(distances, dataset.get(0).unwrap().distances.value);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_distance_noise() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(16, 2.0..6.0)
+            .with_distance_noise(DistanceNoise::Gaussian {
+                std: 0.0,
+            })
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let mut train = dataset.clone().split_for_training(1.0).train;
+        assert!(train.has_noisy_distance());
+        assert_eq!(
+            train.distance_noise(),
+            &DistanceNoise::Gaussian {
+                std: 0.0
+            }
+        );
+
+        let starts = dataset.get(0).unwrap().distances.value;
+        let distances = train.get(0).unwrap().distances.value;
+        for (distance, start) in distances.iter().zip(starts.iter()) {
+            assert!((distance - start - 0.125).abs() < 1e-5);
+        }
+
+        train.set_distance_noise(DistanceNoise::Gaussian {
+            std: 0.5,
+        });
+        for index in 0..4 {
+            let distances = train.get(index).unwrap().distances.value;
+            assert!(distances
+                .iter()
+                .all(|distance| (2.0..6.0).contains(distance)));
+            assert!(distances
+                .chunks_exact(16)
+                .all(|ray| ray.windows(2).all(|pair| pair[0] < pair[1])));
+        }
+
+        train.set_distance_noise(DistanceNoise::Uniform {
+            scale: 0.0,
+        });
+        assert_eq!(train.get(0).unwrap().distances.value, starts);
+
+        train.set_distance_noise(DistanceNoise::None);
+        assert!(!train.has_noisy_distance());
+        assert_eq!(train.get(0).unwrap().distances.value, starts);
+
+        let mut dataset = dataset;
+        dataset.set_distance_noise(DistanceNoise::Uniform {
+            scale: 0.5,
+        });
+        let split = dataset.split_by_indices(&[0]);
+        assert_eq!(
+            split.train.distance_noise(),
+            &DistanceNoise::Uniform {
+                scale: 0.5
+            }
+        );
+        assert!(!split.test.has_noisy_distance());
+    }
+
+    #[test]
+    fn simple_nerf_dataset_intrinsic_accessors() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!(dataset.height(), 100);
+        assert_eq!(dataset.width(), 100);
+        assert!((dataset.focal() - 138.889).abs() < 1e-3);
+
+        assert_eq!(dataset.image_size(), (100, 100));
+        assert!(dataset.pose(dataset.len()).is_none());
+
+        let focal = dataset.focal();
+        let poses = (0..dataset.len())
+            .map(|index| dataset.pose(index).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            poses[1].concat(),
+            dataset.pose_tensor(1).unwrap().into_data().value
+        );
+
+        let datasets = dataset.split_for_training(0.8);
+        let train_count = datasets.train.len();
+        for (dataset, offset) in
+            [(datasets.train, 0), (datasets.test, train_count)]
+        {
+            assert_eq!(dataset.focal(), focal);
+            assert_eq!(dataset.height(), 100);
+            assert_eq!(dataset.width(), 100);
+            assert_eq!(dataset.image_size(), (100, 100));
+            assert_eq!(dataset.pose(0), Some(poses[offset]));
+        }
+    }
+
+    #[test]
+    fn simple_nerf_dataset_ray_batch() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let input = dataset.get(0).unwrap().into_input::<Backend>(&device);
+        let rays = input.clone().into_rays();
+        assert_eq!(rays.directions.dims(), [10000, 7, 3]);
+        assert_eq!(rays.distances.dims(), [10000, 7, 1]);
+        assert_eq!(rays.image.dims(), [10000, 3]);
+        assert_eq!(rays.intervals.dims(), [10000, 7, 1]);
+        assert_eq!(rays.positions.dims(), [10000, 7, 3]);
+        assert!(rays.mask.is_none());
+
+        assert_eq!(
+            rays.positions.reshape([100, 100, 7, 3]).into_data().value,
+            input.positions.into_data().value
+        );
+        assert_eq!(
+            rays.image.reshape([100, 100, 3]).into_data().value,
+            input.image.into_data().value
+        );
+    }
+
+    #[test]
+    fn simple_nerf_dataset_positions_at() {
+        let device = Default::default();
+
+        let input = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .into_input::<Backend>(&device);
+
+        input
+            .positions_at(input.distances.clone())
+            .into_data()
+            .assert_approx_eq(&input.positions.clone().into_data(), 4);
+
+        let distances =
+            Tensor::<Backend, 4>::full([100, 100, 3, 1], 4.0, &device);
+        let positions = input.positions_at(distances);
+        assert_eq!(positions.dims(), [100, 100, 3, 3]);
+        positions.slice([0..100, 0..100, 0..1]).into_data().assert_approx_eq(
+            &input.positions.slice([0..100, 0..100, 4..5]).into_data(),
+            4,
+        );
+    }
+
+    #[test]
+    fn simple_nerf_dataset_target_rgb() {
+        let device = Default::default();
+
+        let values =
+            (0..2 * 3 * 3).map(|index| index as f32 / 18.0).collect::<Vec<_>>();
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 2, 3, 3], values)),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+        let input = SimpleNerfDatasetConfig::new(5, 2.0..6.0)
+            .with_image_layout(ImageLayout::ChannelsLast)
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .into_input::<Backend>(&device);
+        let image = input.image.clone();
+
+        let target = input.target_rgb();
+        assert_eq!(target.dims(), [6, 3]);
+        assert_eq!(
+            target.clone().reshape([2, 3, 3]).into_data().value,
+            image.clone().into_data().value
+        );
+
+        let (x, y) = (2, 1);
+        assert_eq!(
+            target.slice([y * 3 + x..y * 3 + x + 1, 0..3]).into_data().value,
+            image.slice([y..y + 1, x..x + 1, 0..3]).into_data().value
+        );
+    }
+
+    #[test]
+    fn simple_nerf_dataset_ray_sampling() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let input = dataset.get(0).unwrap().into_input::<Backend>(&device);
+
+        let samples = [7, 7, 8].map(|seed| input.sample_rays(256, seed));
+        assert_eq!(samples[0].directions.dims(), [256, 1, 7, 3]);
+        assert_eq!(samples[0].image.dims(), [256, 1, 3]);
+        assert_eq!(samples[0].intervals.dims(), [256, 1, 7, 1]);
+        assert_eq!(samples[0].positions.dims(), [256, 1, 7, 3]);
+
+        let positions =
+            samples.map(|sample| sample.positions.into_data().value);
+        assert_eq!(positions[0], positions[1]);
+        assert_ne!(positions[0], positions[2]);
+
+        let sample = input.sample_rays(20000, 7);
+        assert_eq!(sample.image.dims(), [10000, 1, 3]);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_splitting() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(8, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        let datasets = dataset.split_for_training(0.8);
+        assert_eq!(datasets.train.len(), 85);
+        assert_eq!(datasets.test.len(), 21);
+        assert!(!datasets.test.has_noisy_distance());
+
+        let datasets = datasets.test.split_for_training(1.0);
+        assert_eq!(datasets.train.len(), 21);
+        assert_eq!(datasets.test.len(), 0);
+        assert!(!datasets.test.has_noisy_distance());
+    }
+
+    #[test]
+    fn simple_nerf_dataset_empty_splitting() {
+        let device = Default::default();
+
+        let empty = SimpleNerfDatasetConfig::new(8, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap()
+            .split_for_training(1.0)
+            .test;
+        assert!(empty.is_empty());
+        assert!(empty.get(0).is_none());
+
+        for ratio in [0.0, 0.5, 1.0, f32::NAN] {
+            let datasets = empty.clone().split_for_training(ratio);
+            assert!(datasets.train.is_empty());
+            assert!(datasets.test.is_empty());
+            assert!(datasets.train.has_noisy_distance());
+            assert!(!datasets.test.has_noisy_distance());
+            assert_eq!(datasets.train.image_size(), (100, 100));
+        }
+    }
+
+    #[test]
+    fn simple_nerf_dataset_shuffling() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let permutation = |seed: u64| {
+            let mut shuffled = dataset.clone();
+            shuffled.shuffle(seed);
+            assert_eq!(shuffled.len(), dataset.len());
+            shuffled
+                .inners
+                .iter()
+                .map(|inner| {
+                    dataset.inners.iter().position(|source| source == inner)
+                })
+                .collect::<Option<Vec<_>>>()
+                .unwrap()
+        };
+
+        assert_eq!(permutation(0), permutation(0));
+        assert_ne!(permutation(0), permutation(1));
+        assert_ne!(permutation(0), (0..dataset.len()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn simple_nerf_dataset_splitting_by_indices() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(8, 2.0..6.0)
+            .init_from_file_path::<Backend>(TEST_DATA_FILE_PATH, &device)
+            .unwrap();
+        let dataset_size = dataset.len();
+
+        let datasets = dataset.clone().split_by_indices(&[0, 2, 4, 4, 1000]);
+        assert_eq!(datasets.train.len(), 3);
+        assert_eq!(datasets.test.len(), dataset_size - 3);
+        assert!(datasets.train.has_noisy_distance());
+        assert!(!datasets.test.has_noisy_distance());
+
+        for (index, train_index) in [0, 2, 4].iter().copied().enumerate() {
+            assert_eq!(
+                datasets.train.inners[index].image,
+                dataset.inners[train_index].image
+            );
+        }
+        for (index, test_index) in [1, 3, 5].iter().copied().enumerate() {
+            assert_eq!(
+                datasets.test.inners[index].image,
+                dataset.inners[test_index].image
+            );
+        }
+    }
+}
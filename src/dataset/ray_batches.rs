@@ -0,0 +1,500 @@
+use super::*;
+
+// NOTE: The rays of all images are visited in a permutation,
+// so every ray is drawn once before any ray repeats in an epoch
+#[derive(Clone, Debug)]
+pub struct SimpleNerfRayBatchDataset<B: Backend> {
+    batch_size: usize,
+    dataset: SimpleNerfDataset<B>,
+    permutation: RayPermutation,
+    precrop: Option<f32>,
+    region: [usize; 4],
+    seed: u64,
+}
+
+// NOTE: The rays are permuted by a Feistel network on the smallest power of
+// four covering them, and an index out of range is permuted again until it
+// falls in range, so no list of all rays is stored
+#[derive(Clone, Debug)]
+struct RayPermutation {
+    count: usize,
+    half_bits: u32,
+    keys: [u64; 4],
+}
+
+// NOTE: The rays are gathered from random pixels of all images,
+// so the directions are of [R, P, 3] and the image is of [R, C]
+#[derive(Clone, Debug)]
+pub struct SimpleNerfRayBatchItem {
+    pub depth: Option<Data<f32, 2>>,
+    pub directions: Data<f32, 3>,
+    pub distances: Data<f32, 3>,
+    pub image: Data<f32, 2>,
+    pub intervals: Data<f32, 3>,
+    pub mask: Option<Data<f32, 2>>,
+    pub positions: Data<f32, 3>,
+}
+
+impl<B: Backend> SimpleNerfDataset<B> {
+    // NOTE: The last batch has fewer rays if the batch size does not divide
+    // the ray count of all images
+    pub fn into_ray_batches(
+        self,
+        batch_size: usize,
+        seed: u64,
+    ) -> SimpleNerfRayBatchDataset<B> {
+        let mut batches = SimpleNerfRayBatchDataset {
+            batch_size: batch_size.max(1),
+            permutation: RayPermutation::new(0, &mut StdRng::seed_from_u64(0)),
+            precrop: None,
+            region: [0, 0, self.width, self.height],
+            dataset: self,
+            seed,
+        };
+        batches.permute();
+        batches
+    }
+}
+
+impl<B: Backend> SimpleNerfRayBatchDataset<B> {
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    pub fn ray_count(&self) -> usize {
+        self.permutation.count
+    }
+
+    pub fn dataset(&self) -> &SimpleNerfDataset<B> {
+        &self.dataset
+    }
+
+    // NOTE: Only the rays through the central fraction of the height and
+    // the width are drawn, which helps the first iterations of training
+    pub fn with_precrop(
+        mut self,
+        fraction: f32,
+    ) -> Result<SimpleNerfRayBatchDataset<B>, DatasetError> {
+        self.set_precrop(Some(fraction))?;
+        Ok(self)
+    }
+
+    pub fn precrop(&self) -> Option<f32> {
+        self.precrop
+    }
+
+    // NOTE: The precrop is usually turned off after a few hundred iterations,
+    // and its fraction must be in (0, 1] like the one of a center crop
+    pub fn set_precrop(
+        &mut self,
+        precrop: Option<f32>,
+    ) -> Result<(), DatasetError> {
+        if let Some(fraction) = precrop {
+            if !(fraction > 0.0 && fraction <= 1.0) {
+                return Err(DatasetError::InvalidConfig(format!(
+                    "Precrop fraction {} is not in (0, 1]",
+                    fraction
+                )));
+            }
+        }
+        self.precrop = precrop;
+        self.permute();
+        Ok(())
+    }
+
+    // NOTE: Both the permutation and the jitter of the distances
+    // are renewed for the epoch
+    pub fn set_epoch(
+        &mut self,
+        epoch: usize,
+    ) {
+        self.dataset.set_epoch(epoch);
+        self.permute();
+    }
+
+    fn permute(&mut self) {
+        let mut rng = {
+            let mut seed_bytes = [0; 32];
+            seed_bytes[0..8].copy_from_slice(&self.seed.to_le_bytes());
+            seed_bytes[16..24]
+                .copy_from_slice(&(self.dataset.epoch as u64).to_le_bytes());
+            StdRng::from_seed(seed_bytes)
+        };
+        let height = self.dataset.height;
+        let width = self.dataset.width;
+        self.region = match self.precrop {
+            Some(fraction) => center_region(width, height, fraction),
+            None => [0, 0, width, height],
+        };
+        let [_, _, crop_width, crop_height] = self.region;
+        self.permutation = RayPermutation::new(
+            self.dataset.len() * crop_height * crop_width,
+            &mut rng,
+        );
+    }
+}
+
+impl RayPermutation {
+    fn new(
+        count: usize,
+        rng: &mut impl Rng,
+    ) -> RayPermutation {
+        let mut half_bits = 0;
+        while (1_usize << (2 * half_bits)) < count {
+            half_bits += 1;
+        }
+        RayPermutation {
+            count,
+            half_bits,
+            keys: rng.gen(),
+        }
+    }
+
+    fn get(
+        &self,
+        index: usize,
+    ) -> usize {
+        let mask = (1_u64 << self.half_bits) - 1;
+        let mut index = index as u64;
+        loop {
+            let mut left = index >> self.half_bits;
+            let mut right = index & mask;
+            for key in self.keys.iter() {
+                let round = mix(right ^ key) & mask;
+                let next = left ^ round;
+                left = right;
+                right = next;
+            }
+            index = (left << self.half_bits) | right;
+            if index < self.count as u64 {
+                return index as usize;
+            }
+        }
+    }
+}
+
+// NOTE: The finalizer of SplitMix64 scatters the bits of the value
+fn mix(value: u64) -> u64 {
+    let value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    let value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+    value ^ (value >> 31)
+}
+
+impl<B: Backend> Dataset<SimpleNerfRayBatchItem>
+    for SimpleNerfRayBatchDataset<B>
+{
+    fn len(&self) -> usize {
+        self.permutation.count.div_ceil(self.batch_size)
+    }
+
+    // NOTE: Only the selected rays are built from the images, and their
+    // distances are jittered as in the dataset items
+    fn get(
+        &self,
+        index: usize,
+    ) -> Option<SimpleNerfRayBatchItem> {
+        let start = index.checked_mul(self.batch_size)?;
+        if start >= self.permutation.count {
+            return None;
+        }
+        let end = (start + self.batch_size).min(self.permutation.count);
+
+        let dataset = &self.dataset;
+        let ray_count = end - start;
+        let points_per_ray = dataset.config.points_per_ray;
+        let channel_count = dataset.channel_count;
+        let [left, top, crop_width, crop_height] = self.region;
+        let mut rng = match dataset.seed {
+            Some(seed) => {
+                let mut seed_bytes = [0; 32];
+                seed_bytes[0..8].copy_from_slice(&seed.to_le_bytes());
+                seed_bytes[8..16]
+                    .copy_from_slice(&(index as u64).to_le_bytes());
+                seed_bytes[16..24]
+                    .copy_from_slice(&(dataset.epoch as u64).to_le_bytes());
+                // NOTE: The stream differs from the one of the image items
+                seed_bytes[24] = 1;
+                StdRng::from_seed(seed_bytes)
+            },
+            None => StdRng::from_entropy(),
+        };
+
+        // NOTE: The depths and masks are only kept if every image has one
+        let mut depth = dataset
+            .inners
+            .iter()
+            .all(|inner| inner.depth.is_some())
+            .then(|| vec![0.0; ray_count]);
+        let mut mask = dataset
+            .inners
+            .iter()
+            .all(|inner| inner.mask.is_some())
+            .then(|| vec![0.0; ray_count]);
+        let mut directions = Vec::with_capacity(ray_count * points_per_ray * 3);
+        let mut distances = Vec::with_capacity(ray_count * points_per_ray);
+        let mut image = Vec::with_capacity(ray_count * channel_count);
+        let mut intervals = Vec::with_capacity(ray_count * points_per_ray);
+        let mut positions = Vec::with_capacity(ray_count * points_per_ray * 3);
+        for (slot, ray) in (start..end).enumerate() {
+            let ray = self.permutation.get(ray);
+            let inner = &dataset.inners[ray / (crop_width * crop_height)];
+            let y = top + ray / crop_width % crop_height;
+            let x = left + ray % crop_width;
+            let pixel = y * dataset.width + x;
+
+            let origin = &inner.origins.value[pixel * 3..pixel * 3 + 3];
+            let direction = &inner.directions.value
+                [pixel * points_per_ray * 3..pixel * points_per_ray * 3 + 3];
+            let mut ray_distances = inner.distances.clone();
+            if dataset.is_stratified() {
+                for (distance, width) in
+                    ray_distances.iter_mut().zip(jitter_widths(inner))
+                {
+                    *distance +=
+                        dataset.distance_noise.sample(&mut rng) * width;
+                }
+            }
+            if let Some(scales) = &inner.distance_scales {
+                for distance in ray_distances.iter_mut() {
+                    *distance *= scales[pixel];
+                }
+            }
+
+            for (index, distance) in ray_distances.iter().enumerate() {
+                directions.extend_from_slice(direction);
+                distances.push(*distance);
+                intervals.push(match ray_distances.get(index + 1) {
+                    Some(next) => next - distance,
+                    None => 1e9,
+                });
+                positions.extend(
+                    [0, 1, 2]
+                        .map(|axis| origin[axis] + direction[axis] * distance),
+                );
+            }
+            image.extend_from_slice(
+                &inner.image.value
+                    [pixel * channel_count..(pixel + 1) * channel_count],
+            );
+            if let (Some(depth), Some(data)) = (&mut depth, &inner.depth) {
+                depth[slot] = data.value[pixel];
+            }
+            if let (Some(mask), Some(data)) = (&mut mask, &inner.mask) {
+                mask[slot] = data.value[pixel];
+            }
+        }
+
+        Some(SimpleNerfRayBatchItem {
+            depth: depth
+                .map(|depth| Data::new(depth, Shape::new([ray_count, 1]))),
+            directions: Data::new(
+                directions,
+                Shape::new([ray_count, points_per_ray, 3]),
+            ),
+            distances: Data::new(
+                distances,
+                Shape::new([ray_count, points_per_ray, 1]),
+            ),
+            image: Data::new(image, Shape::new([ray_count, channel_count])),
+            intervals: Data::new(
+                intervals,
+                Shape::new([ray_count, points_per_ray, 1]),
+            ),
+            mask: mask.map(|mask| Data::new(mask, Shape::new([ray_count, 1]))),
+            positions: Data::new(
+                positions,
+                Shape::new([ray_count, points_per_ray, 3]),
+            ),
+        })
+    }
+}
+
+impl SimpleNerfRayBatchItem {
+    pub fn into_rays<B: Backend>(
+        self,
+        device: &B::Device,
+    ) -> RayBatch<B> {
+        RayBatch {
+            depth: self
+                .depth
+                .map(|depth| Tensor::from_data(depth.convert(), device)),
+            directions: Tensor::from_data(self.directions.convert(), device),
+            distances: Tensor::from_data(self.distances.convert(), device),
+            image: Tensor::from_data(self.image.convert(), device),
+            intervals: Tensor::from_data(self.intervals.convert(), device),
+            mask: self
+                .mask
+                .map(|mask| Tensor::from_data(mask.convert(), device)),
+            positions: Tensor::from_data(self.positions.convert(), device),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::*;
+    use super::*;
+
+    type Backend = burn::backend::Wgpu;
+
+    #[test]
+    fn simple_nerf_dataset_ray_batches() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            (
+                "images",
+                npy_bytes(
+                    &[2, 2, 5, 3],
+                    (0..60).map(|index| index as f32 / 59.0).collect(),
+                ),
+            ),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+        let mut dataset = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap()
+            .with_seed(5);
+        dataset.set_distance_noise(DistanceNoise::Uniform {
+            scale: 1.0,
+        });
+        let mut batches = dataset.into_ray_batches(3, 7);
+        assert_eq!(batches.ray_count(), 20);
+        assert_eq!(batches.len(), 7);
+        assert!(batches.get(7).is_none());
+
+        let items = batches.iter().collect::<Vec<_>>();
+        assert_eq!(items[0].directions.shape.dims, [3, 4, 3]);
+        assert_eq!(items[0].distances.shape.dims, [3, 4, 1]);
+        assert_eq!(items[0].image.shape.dims, [3, 3]);
+        assert_eq!(items[0].intervals.shape.dims, [3, 4, 1]);
+        assert_eq!(items[0].positions.shape.dims, [3, 4, 3]);
+        assert_eq!(items[6].image.shape.dims, [2, 3]);
+        assert!(items[0].depth.is_none());
+        assert!(items[0].mask.is_none());
+
+        // NOTE: The colors tell the rays apart, and each ray is drawn once
+        let mut rays = items
+            .iter()
+            .flat_map(|item| item.image.value.chunks_exact(3))
+            .map(|color| (color[0] * 59.0).round() as usize / 3)
+            .collect::<Vec<_>>();
+        assert_ne!(rays, (0..20).collect::<Vec<_>>());
+        rays.sort_unstable();
+        assert_eq!(rays, (0..20).collect::<Vec<_>>());
+
+        for item in &items {
+            let origins = item
+                .positions
+                .value
+                .chunks_exact(3)
+                .zip(item.directions.value.chunks_exact(3))
+                .zip(&item.distances.value)
+                .map(|((position, direction), distance)| {
+                    [0, 1, 2]
+                        .map(|axis| position[axis] - direction[axis] * distance)
+                });
+            for origin in origins {
+                assert!((origin[2] - 4.0).abs() < 1e-4);
+            }
+            for distances in item.distances.value.chunks_exact(4) {
+                assert!(distances.windows(2).all(|pair| pair[0] < pair[1]));
+                assert!(distances
+                    .iter()
+                    .all(|distance| { (2.0..6.0).contains(distance) }));
+            }
+        }
+        assert!(items[0]
+            .distances
+            .value
+            .iter()
+            .any(|distance| distance.fract() != 0.0));
+
+        let item = batches.get(0).unwrap();
+        assert_eq!(item.image, items[0].image);
+        assert_eq!(item.distances, items[0].distances);
+        let rays = item.into_rays::<Backend>(&device);
+        assert_eq!(rays.positions.dims(), [3, 4, 3]);
+
+        batches.set_epoch(1);
+        let item = batches.get(0).unwrap();
+        assert_ne!(
+            (item.image.value, item.distances.value),
+            (items[0].image.value.clone(), items[0].distances.value.clone())
+        );
+    }
+
+    #[test]
+    fn ray_permutation_is_bijective() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for count in [1, 2, 5, 17, 64, 100] {
+            let permutation = RayPermutation::new(count, &mut rng);
+            let mut indices = (0..count)
+                .map(|index| permutation.get(index))
+                .collect::<Vec<_>>();
+            if count > 2 {
+                assert_ne!(indices, (0..count).collect::<Vec<_>>());
+            }
+            indices.sort_unstable();
+            assert_eq!(indices, (0..count).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn simple_nerf_dataset_ray_batches_precrop() {
+        let device = Default::default();
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![4.0_f32])),
+            (
+                "images",
+                npy_bytes(
+                    &[2, 6, 8, 3],
+                    (0..288).map(|index| index as f32 / 287.0).collect(),
+                ),
+            ),
+            ("poses", npy_bytes(&[2, 3, 4], identity_poses(2))),
+        ]);
+        let mut batches = SimpleNerfDatasetConfig::new(4, 2.0..6.0)
+            .init_from_bytes::<Backend>(&archive, &device)
+            .unwrap()
+            .into_ray_batches(5, 7)
+            .with_precrop(0.5)
+            .unwrap();
+        assert_eq!(batches.precrop(), Some(0.5));
+        assert_eq!(batches.ray_count(), 24);
+        assert_eq!(batches.len(), 5);
+
+        // NOTE: The central half of 8x6 pixels is 4x3 from (2, 1)
+        let mut pixels = batches
+            .iter()
+            .flat_map(|item| {
+                item.image
+                    .value
+                    .chunks_exact(3)
+                    .map(|color| (color[0] * 287.0).round() as usize / 3)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        for pixel in &pixels {
+            let (y, x) = (pixel % 48 / 8, pixel % 8);
+            assert!((1..4).contains(&y) && (2..6).contains(&x), "{}", pixel);
+        }
+        pixels.sort_unstable();
+        pixels.dedup();
+        assert_eq!(pixels.len(), 24);
+
+        for fraction in [0.0, -0.5, 1.5, f32::NAN] {
+            assert!(matches!(
+                batches.set_precrop(Some(fraction)),
+                Err(DatasetError::InvalidConfig(_))
+            ));
+        }
+        assert_eq!(batches.precrop(), Some(0.5));
+
+        batches.set_precrop(None).unwrap();
+        assert_eq!(batches.ray_count(), 96);
+        assert_eq!(batches.len(), 20);
+    }
+}
@@ -0,0 +1,785 @@
+use super::*;
+
+use reqwest::{
+    blocking::{Client, Response},
+    header::{HeaderName, ACCEPT_RANGES, CONTENT_LENGTH, ETAG, RANGE},
+    IntoUrl, StatusCode,
+};
+use sha2::{Digest, Sha256};
+use std::{fs, io::Read, thread, time::Duration};
+
+#[derive(Config, Debug)]
+pub struct DownloadConfig {
+    #[config(default = 30.0)]
+    pub timeout_seconds: f64,
+    #[config(default = 3)]
+    pub retry_count: usize,
+    #[config(default = 1.0)]
+    pub backoff_seconds: f64,
+    // NOTE: A cached archive is used without any request by default,
+    // and it is checked against the ETag and the size on the server if set
+    #[config(default = false)]
+    pub revalidate_cache: bool,
+    pub sha256: Option<String>,
+}
+
+impl SimpleNerfDatasetConfig {
+    // NOTE: The file URLs and the paths without a scheme are read locally
+    pub fn init_from_url<B: Backend>(
+        &self,
+        url: impl AsRef<str>,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        let url = url.as_ref();
+        match local_path(url)? {
+            Some(path) => self.init_from_file_path(path, device),
+            None => self.init_from_url_with_progress(url, device, |_, _| {}),
+        }
+    }
+
+    // NOTE: The client can be built with default headers for authorization,
+    // and its timeout is used instead of the one in the download config
+    pub fn init_from_url_with_client<B: Backend>(
+        &self,
+        url: impl IntoUrl + Clone,
+        client: &Client,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        let (bytes, _) =
+            download_bytes(url, client, &self.download, |_, _| {})?;
+        self.init_from_bytes(bytes, device)
+    }
+
+    // NOTE: The progress is reported as the downloaded and total byte counts,
+    // and it is reported once more with both counts equal at the end
+    pub fn init_from_url_with_progress<B: Backend>(
+        &self,
+        url: impl IntoUrl + Clone,
+        device: &B::Device,
+        progress: impl FnMut(u64, Option<u64>),
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        let (bytes, _) = download_bytes(
+            url,
+            &build_client(&self.download)?,
+            &self.download,
+            progress,
+        )?;
+        self.init_from_bytes(bytes, device)
+    }
+
+    // NOTE: The download and the parsing are both blocking, so they run
+    // together on the blocking thread pool once the future is polled
+    #[cfg(feature = "async")]
+    pub fn init_from_url_async<B: Backend>(
+        &self,
+        url: impl AsRef<str>,
+        device: &B::Device,
+    ) -> DatasetFuture<B> {
+        let config = self.clone();
+        let url = url.as_ref().to_owned();
+        let device = device.clone();
+        DatasetFuture {
+            handle: None,
+            task: Some(Box::new(move || {
+                config.init_from_url::<B>(url, &device)
+            })),
+        }
+    }
+
+    pub fn init_from_url_cached<B: Backend>(
+        &self,
+        url: &str,
+        cache_directory: impl AsRef<Path>,
+        device: &B::Device,
+    ) -> Result<SimpleNerfDataset<B>, DatasetError> {
+        let cache_directory = cache_directory.as_ref();
+        let cache_file_path =
+            cache_directory.join(format!("{:x}.npz", Sha256::digest(url)));
+        let etag_file_path = cache_file_path.with_extension("etag");
+
+        // NOTE: A corrupted, partial or stale cache file is re-fetched
+        if cache_file_path.is_file() {
+            if ZipArchive::new(File::open(&cache_file_path)?).is_ok()
+                && (!self.download.revalidate_cache
+                    || is_cache_fresh(
+                        url,
+                        &cache_file_path,
+                        &etag_file_path,
+                        &self.download,
+                    )?)
+            {
+                return self.init_from_file_path(&cache_file_path, device);
+            }
+            fs::remove_file(&cache_file_path)?;
+        }
+
+        let (bytes, etag) = download_bytes(
+            url,
+            &build_client(&self.download)?,
+            &self.download,
+            |_, _| {},
+        )?;
+        let partial_file_path = cache_file_path.with_extension("npz.part");
+        fs::create_dir_all(cache_directory)?;
+        fs::write(&partial_file_path, &bytes)?;
+        fs::rename(&partial_file_path, &cache_file_path)?;
+        match etag {
+            Some(etag) => fs::write(&etag_file_path, etag)?,
+            None if etag_file_path.is_file() => {
+                fs::remove_file(&etag_file_path)?
+            },
+            None => {},
+        }
+
+        self.init_from_bytes(bytes, device)
+    }
+}
+
+// NOTE: The crate is in edition 2015 without async functions,
+// so the future of the blocking task is named here
+#[cfg(feature = "async")]
+pub struct DatasetFuture<B: Backend> {
+    handle: Option<
+        tokio::task::JoinHandle<Result<SimpleNerfDataset<B>, DatasetError>>,
+    >,
+    task: Option<DatasetTask<B>>,
+}
+
+#[cfg(feature = "async")]
+type DatasetTask<B> =
+    Box<dyn FnOnce() -> Result<SimpleNerfDataset<B>, DatasetError> + Send>;
+
+#[cfg(feature = "async")]
+impl<B: Backend> std::future::Future for DatasetFuture<B> {
+    type Output = Result<SimpleNerfDataset<B>, DatasetError>;
+
+    // NOTE: The task is spawned at the first poll, so the future can be
+    // created outside of a runtime
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        context: &mut std::task::Context,
+    ) -> std::task::Poll<Self::Output> {
+        let future = self.get_mut();
+        if let Some(task) = future.task.take() {
+            future.handle = Some(tokio::task::spawn_blocking(task));
+        }
+        match future.handle.as_mut() {
+            Some(handle) => std::pin::Pin::new(handle)
+                .poll(context)
+                .map(|result| result.map_err(io::Error::other)?),
+            None => std::task::Poll::Pending,
+        }
+    }
+}
+
+// NOTE: The scheme has at least two letters, so a drive letter like "C:"
+// is a part of a path rather than a scheme
+fn local_path(url: &str) -> Result<Option<PathBuf>, DatasetError> {
+    let scheme = Regex::new(r"^([A-Za-z][A-Za-z0-9+.-]+)://").unwrap();
+    match scheme.captures(url) {
+        Some(captures) if captures[1].eq_ignore_ascii_case("file") => {
+            reqwest::Url::parse(url)
+                .ok()
+                .and_then(|url| url.to_file_path().ok())
+                .map(Some)
+                .ok_or_else(|| {
+                    DatasetError::InvalidConfig(format!(
+                        "File URL {:?} is not a local path",
+                        url
+                    ))
+                })
+        },
+        Some(_) => Ok(None),
+        None => Ok(Some(PathBuf::from(url))),
+    }
+}
+
+fn build_client(config: &DownloadConfig) -> Result<Client, DatasetError> {
+    Ok(Client::builder()
+        .timeout(Duration::from_secs_f64(config.timeout_seconds))
+        .build()?)
+}
+
+// NOTE: Timeouts, connection failures and server errors are retried
+// with exponential backoff, while other errors fail fast
+fn fetch_response_with_client(
+    url: impl IntoUrl + Clone,
+    client: &Client,
+    config: &DownloadConfig,
+) -> Result<Response, DatasetError> {
+    let mut attempt_count = 0;
+    loop {
+        attempt_count += 1;
+        let error = match client
+            .get(url.clone())
+            .send()
+            .and_then(|response| response.error_for_status())
+        {
+            Ok(response) => return Ok(response),
+            Err(error) => error,
+        };
+
+        let status = error.status();
+        let kind = match status {
+            Some(StatusCode::NOT_FOUND) => Some(io::ErrorKind::NotFound),
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                Some(io::ErrorKind::PermissionDenied)
+            },
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            return Err(DatasetError::Io(io::Error::new(kind, error)));
+        }
+        let is_transient = error.is_timeout()
+            || error.is_connect()
+            || status.is_some_and(|status| status.is_server_error());
+        if !is_transient {
+            return Err(error.into());
+        }
+        if attempt_count > config.retry_count {
+            return Err(DatasetError::Download {
+                attempt_count,
+                status,
+                source: error,
+            });
+        }
+
+        thread::sleep(Duration::from_secs_f64(
+            config.backoff_seconds * 2.0_f64.powi(attempt_count as i32 - 1),
+        ));
+    }
+}
+
+// NOTE: An interrupted or truncated body is resumed with a range request
+// if the server accepts byte ranges, and the checksum is verified at the end.
+// The bytes are returned with the ETag of the first response if any
+fn download_bytes(
+    url: impl IntoUrl + Clone,
+    client: &Client,
+    config: &DownloadConfig,
+    mut progress: impl FnMut(u64, Option<u64>),
+) -> Result<(Vec<u8>, Option<String>), DatasetError> {
+    let mut response = fetch_response_with_client(url.clone(), client, config)?;
+    let etag = header_value(&response, ETAG);
+    let total = response.content_length();
+    let is_resumable = header_value(&response, ACCEPT_RANGES)
+        .is_some_and(|value| value.eq_ignore_ascii_case("bytes"));
+
+    let mut bytes = Vec::with_capacity(total.unwrap_or_default() as usize);
+    let mut chunk = vec![0; 1 << 16];
+    let mut resume_count = 0;
+    loop {
+        let error = match response.read(&mut chunk) {
+            Ok(0) if total.is_none_or(|total| total <= bytes.len() as u64) => {
+                break
+            },
+            Ok(0) => io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Download ended at {} of {} bytes",
+                    bytes.len(),
+                    total.unwrap_or_default()
+                ),
+            ),
+            Ok(size) => {
+                bytes.extend_from_slice(&chunk[..size]);
+                progress(bytes.len() as u64, total);
+                continue;
+            },
+            Err(error) => error,
+        };
+        if !is_resumable || resume_count >= config.retry_count {
+            return Err(error.into());
+        }
+        resume_count += 1;
+
+        // NOTE: The server may ignore the range and send the whole body again
+        response = client
+            .get(url.clone())
+            .header(RANGE, format!("bytes={}-", bytes.len()))
+            .send()
+            .and_then(|response| response.error_for_status())?;
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            bytes.clear();
+        }
+    }
+    progress(bytes.len() as u64, Some(bytes.len() as u64));
+
+    verify_checksum(&bytes, config)?;
+    Ok((bytes, etag))
+}
+
+fn verify_checksum(
+    bytes: &[u8],
+    config: &DownloadConfig,
+) -> Result<(), DatasetError> {
+    if let Some(expected) = &config.sha256 {
+        let got = format!("{:x}", Sha256::digest(bytes));
+        if !got.eq_ignore_ascii_case(expected.trim()) {
+            return Err(DatasetError::ChecksumMismatch {
+                expected: expected.clone(),
+                got,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn header_value(
+    response: &Response,
+    name: HeaderName,
+) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+// NOTE: The cache is kept if the server is unreachable or reports nothing
+fn is_cache_fresh(
+    url: &str,
+    cache_file_path: &Path,
+    etag_file_path: &Path,
+    config: &DownloadConfig,
+) -> Result<bool, DatasetError> {
+    let response = match build_client(config)?
+        .head(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+    {
+        Ok(response) => response,
+        Err(_) => return Ok(true),
+    };
+
+    if let Some(content_length) = header_value(&response, CONTENT_LENGTH)
+        .and_then(|value| value.parse::<u64>().ok())
+    {
+        if content_length != fs::metadata(cache_file_path)?.len() {
+            return Ok(false);
+        }
+    }
+
+    if let Some(etag) = header_value(&response, ETAG) {
+        if etag_file_path.is_file()
+            && fs::read_to_string(etag_file_path)? != etag
+        {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::*;
+    use super::*;
+    use std::{
+        io::{BufRead, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    type Backend = burn::backend::Wgpu;
+
+    fn serve(responses: Vec<Vec<u8>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader =
+                    io::BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                stream.write_all(&response).unwrap();
+            }
+        });
+        format!("http://{}/data.npz", address)
+    }
+
+    fn http_head(
+        status: &str,
+        etag: &str,
+        content_length: usize,
+    ) -> Vec<u8> {
+        format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nETag: {}\r\nConnection: close\r\n\r\n",
+            status, content_length, etag
+        )
+        .into_bytes()
+    }
+
+    fn http_response(
+        status: &str,
+        etag: &str,
+        body: &[u8],
+    ) -> Vec<u8> {
+        [http_head(status, etag, body.len()).as_slice(), body].concat()
+    }
+
+    // NOTE: The response is only served with the given authorization
+    fn serve_authorized(
+        authorization: &str,
+        body: Vec<u8>,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let authorization = format!("authorization: {}", authorization);
+        thread::spawn(move || loop {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = io::BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            let mut is_authorized = false;
+            while reader.read_line(&mut line).unwrap() > 2 {
+                is_authorized |=
+                    line.trim_end().eq_ignore_ascii_case(&authorization);
+                line.clear();
+            }
+            let response = if is_authorized {
+                http_response("200 OK", "\"0\"", &body)
+            } else {
+                http_response("401 Unauthorized", "\"0\"", &[])
+            };
+            stream.write_all(&response).unwrap();
+        });
+        format!("http://{}/data.npz", address)
+    }
+
+    #[test]
+    fn simple_nerf_dataset_remote_retrieval() {
+        let device = Default::default();
+
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_url::<Backend>(TEST_DATA_URL, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let dataset = dataset.unwrap();
+        assert_eq!(dataset.inners.len(), 106);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_local_urls() {
+        let device = Default::default();
+        let config = SimpleNerfDatasetConfig::new(7, 2.0..6.0);
+
+        let file_path = fs::canonicalize(TEST_DATA_FILE_PATH).unwrap();
+        let file_url = reqwest::Url::from_file_path(&file_path).unwrap();
+        for url in [TEST_DATA_FILE_PATH, file_url.as_str()] {
+            let dataset = config.init_from_url::<Backend>(url, &device);
+            assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+            assert_eq!(dataset.unwrap().len(), 106);
+        }
+
+        assert_eq!(local_path(TEST_DATA_URL).unwrap(), None);
+        assert_eq!(local_path("s3://bucket/data.npz").unwrap(), None);
+        for path in [
+            "data.npz",
+            "/data/lego/data.npz",
+            "C:\\data\\lego\\data.npz",
+            "C:/data/lego/data.npz",
+            "\\\\server\\share\\data.npz",
+        ] {
+            assert_eq!(local_path(path).unwrap(), Some(PathBuf::from(path)));
+        }
+
+        #[cfg(unix)]
+        assert_eq!(
+            local_path("file:///data/lego/data.npz").unwrap(),
+            Some(PathBuf::from("/data/lego/data.npz"))
+        );
+        #[cfg(windows)]
+        assert_eq!(
+            local_path("file:///C:/data/lego/data.npz").unwrap(),
+            Some(PathBuf::from("C:\\data\\lego\\data.npz"))
+        );
+        #[cfg(unix)]
+        assert!(matches!(
+            local_path("file://host/data.npz").unwrap_err(),
+            DatasetError::InvalidConfig(_)
+        ));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_cached_retrieval() {
+        let device = Default::default();
+
+        // NOTE: The URL is unreachable, so only the cache can be hit
+        let url = "http://127.0.0.1:9/data.npz";
+        let cache_directory = temp_directory("cache");
+        let cache_file_path =
+            cache_directory.join(format!("{:x}.npz", Sha256::digest(url)));
+        fs::copy(TEST_DATA_FILE_PATH, &cache_file_path).unwrap();
+
+        let config = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .with_download(DownloadConfig::new().with_retry_count(0));
+        let dataset = config.init_from_url_cached::<Backend>(
+            url,
+            &cache_directory,
+            &device,
+        );
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+        assert_eq!(dataset.unwrap().inners.len(), 106);
+
+        fs::write(&cache_file_path, b"corrupted").unwrap();
+        let dataset = config.init_from_url_cached::<Backend>(
+            url,
+            &cache_directory,
+            &device,
+        );
+        assert!(dataset.is_err());
+        assert!(!cache_file_path.exists());
+    }
+
+    #[test]
+    fn simple_nerf_dataset_cache_invalidation() {
+        let device = Default::default();
+
+        let bytes = fs::read(TEST_DATA_FILE_PATH).unwrap();
+        let url = serve(vec![
+            http_response("200 OK", "\"0\"", &bytes),
+            http_head("200 OK", "\"0\"", bytes.len()),
+            http_head("200 OK", "\"1\"", bytes.len()),
+            http_response("200 OK", "\"1\"", &bytes),
+        ]);
+        let cache_directory = temp_directory("cache-invalidation");
+        let etag_file_path =
+            cache_directory.join(format!("{:x}.etag", Sha256::digest(&url)));
+
+        // NOTE: Each request consumes one response, so the calls without
+        // revalidation must send no request, the first revalidation must keep
+        // the cache and the second one must re-fetch
+        for (revalidate_cache, etag) in [
+            (false, "\"0\""),
+            (true, "\"0\""),
+            (false, "\"0\""),
+            (true, "\"1\""),
+        ] {
+            let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+                .with_download(
+                    DownloadConfig::new()
+                        .with_revalidate_cache(revalidate_cache),
+                )
+                .init_from_url_cached::<Backend>(
+                    &url,
+                    &cache_directory,
+                    &device,
+                );
+            assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+            assert_eq!(fs::read_to_string(&etag_file_path).unwrap(), etag);
+        }
+    }
+
+    #[test]
+    fn simple_nerf_dataset_remote_progress() {
+        let device = Default::default();
+
+        let bytes = fs::read(TEST_DATA_FILE_PATH).unwrap();
+        let url = serve(vec![http_response("200 OK", "\"0\"", &bytes)]);
+        let mut progresses = vec![];
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_url_with_progress::<Backend>(
+                url,
+                &device,
+                |downloaded, total| progresses.push((downloaded, total)),
+            );
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let size = bytes.len() as u64;
+        assert!(progresses.len() > 1);
+        assert!(progresses.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        assert!(progresses.iter().all(|(_, total)| *total == Some(size)));
+        assert_eq!(progresses.last(), Some(&(size, Some(size))));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_download_retries() {
+        let device = Default::default();
+        let config = SimpleNerfDatasetConfig::new(7, 2.0..6.0).with_download(
+            DownloadConfig::new().with_retry_count(1).with_backoff_seconds(0.0),
+        );
+
+        let url = serve(vec![
+            http_response("503 Service Unavailable", "\"0\"", &[]),
+            http_response(
+                "200 OK",
+                "\"0\"",
+                &fs::read(TEST_DATA_FILE_PATH).unwrap(),
+            ),
+        ]);
+        let dataset = config.init_from_url::<Backend>(url, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let url = serve(vec![
+            http_response("503 Service Unavailable", "\"0\"", &[]),
+            http_response("503 Service Unavailable", "\"0\"", &[]),
+        ]);
+        let dataset = config.init_from_url::<Backend>(url, &device);
+        assert!(matches!(
+            dataset.unwrap_err(),
+            DatasetError::Download {
+                attempt_count: 2,
+                status: Some(StatusCode::SERVICE_UNAVAILABLE),
+                ..
+            }
+        ));
+
+        let url = serve(vec![
+            http_response("503 Service Unavailable", "\"0\"", &[]),
+            http_response("503 Service Unavailable", "\"0\"", &[]),
+            http_response(
+                "200 OK",
+                "\"0\"",
+                &fs::read(TEST_DATA_FILE_PATH).unwrap(),
+            ),
+        ]);
+        let dataset = config
+            .clone()
+            .with_download(
+                DownloadConfig::new()
+                    .with_retry_count(2)
+                    .with_backoff_seconds(0.0),
+            )
+            .init_from_url::<Backend>(url, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+
+        let url = serve(vec![http_response("404 Not Found", "\"0\"", &[])]);
+        let dataset = config.init_from_url::<Backend>(url, &device);
+        assert_eq!(
+            io::Error::from(dataset.unwrap_err()).kind(),
+            io::ErrorKind::NotFound
+        );
+
+        // NOTE: The server only answers once, so a retry would fail to connect
+        let url = serve(vec![http_response("400 Bad Request", "\"0\"", &[])]);
+        let dataset = config.init_from_url::<Backend>(url, &device);
+        assert!(matches!(dataset.unwrap_err(), DatasetError::Network(_)));
+    }
+
+    #[test]
+    fn simple_nerf_dataset_resumed_retrieval() {
+        let device = Default::default();
+
+        let bytes = fs::read(TEST_DATA_FILE_PATH).unwrap();
+        let half = bytes.len() / 2;
+        let url = serve(vec![
+            [
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\
+                     Accept-Ranges: bytes\r\nConnection: close\r\n\r\n",
+                    bytes.len()
+                )
+                .as_bytes(),
+                &bytes[..half],
+            ]
+            .concat(),
+            http_response("206 Partial Content", "\"0\"", &bytes[half..]),
+        ]);
+        let checksum = format!("{:x}", Sha256::digest(&bytes));
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .with_download(DownloadConfig::new().with_sha256(Some(checksum)))
+            .init_from_url::<Backend>(url, &device);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+        assert_eq!(dataset.unwrap().len(), 106);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_checksum_mismatch() {
+        let device = Default::default();
+        let config = SimpleNerfDatasetConfig::new(7, 2.0..6.0).with_download(
+            DownloadConfig::new().with_sha256(Some("0".repeat(64))),
+        );
+
+        // NOTE: The body is not an archive, so only the checksum can fail
+        let url = serve(vec![http_response("200 OK", "\"0\"", b"corrupted")]);
+        let error = config.init_from_url::<Backend>(url, &device).unwrap_err();
+        assert!(
+            matches!(error, DatasetError::ChecksumMismatch { ref got, .. }
+                if *got == format!("{:x}", Sha256::digest(b"corrupted"))),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn simple_nerf_dataset_authorized_retrieval() {
+        let device = Default::default();
+        let config = SimpleNerfDatasetConfig::new(7, 2.0..6.0);
+
+        let url = serve_authorized(
+            "Bearer token",
+            fs::read(TEST_DATA_FILE_PATH).unwrap(),
+        );
+        let dataset = config.init_from_url::<Backend>(url.as_str(), &device);
+        assert_eq!(
+            io::Error::from(dataset.unwrap_err()).kind(),
+            io::ErrorKind::PermissionDenied
+        );
+
+        let client = Client::builder()
+            .default_headers(
+                vec![(
+                    reqwest::header::AUTHORIZATION,
+                    "Bearer token".parse().unwrap(),
+                )]
+                .into_iter()
+                .collect(),
+            )
+            .build()
+            .unwrap();
+        let dataset = config.init_from_url_with_client::<Backend>(
+            url.as_str(),
+            &client,
+            &device,
+        );
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+        assert_eq!(dataset.unwrap().inners.len(), 106);
+    }
+
+    #[test]
+    fn simple_nerf_dataset_server_errors() {
+        let device = Default::default();
+        let config = SimpleNerfDatasetConfig::new(7, 2.0..6.0).with_download(
+            DownloadConfig::new().with_retry_count(0).with_backoff_seconds(0.0),
+        );
+
+        let url = serve(vec![http_response("403 Forbidden", "\"0\"", &[])]);
+        let dataset = config.init_from_url::<Backend>(url, &device);
+        assert_eq!(
+            io::Error::from(dataset.unwrap_err()).kind(),
+            io::ErrorKind::PermissionDenied
+        );
+
+        let url = serve(vec![http_response("502 Bad Gateway", "\"0\"", &[])]);
+        let error = io::Error::from(
+            config.init_from_url::<Backend>(url, &device).unwrap_err(),
+        );
+        assert_eq!(error.kind(), io::ErrorKind::Other);
+        assert!(error.to_string().contains("502 Bad Gateway"), "{}", error);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn simple_nerf_dataset_async_remote_retrieval() {
+        let device = Default::default();
+
+        let url = serve(vec![http_response(
+            "200 OK",
+            "\"0\"",
+            &fs::read(TEST_DATA_FILE_PATH).unwrap(),
+        )]);
+        // NOTE: The future is created before the runtime
+        let dataset = SimpleNerfDatasetConfig::new(7, 2.0..6.0)
+            .init_from_url_async::<Backend>(url, &device);
+        let dataset = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(dataset);
+        assert!(dataset.is_ok(), "Error: {}", dataset.unwrap_err());
+        assert_eq!(dataset.unwrap().inners.len(), 106);
+    }
+}
@@ -0,0 +1,146 @@
+use super::*;
+
+// NOTE: The rays have the same layout as the dataset items without images,
+// and the origins are of [H, W, 1, 3]
+#[derive(Clone, Debug)]
+pub struct SimpleNerfCameraRays {
+    pub directions: Data<f32, 4>,
+    pub distances: Data<f32, 4>,
+    pub intervals: Data<f32, 4>,
+    pub origins: Data<f32, 4>,
+    pub positions: Data<f32, 4>,
+}
+
+impl SimpleNerfDatasetConfig {
+    // NOTE: The render poses share the image size and the first camera
+    // of the images in the archive, and the crop and downscale also apply
+    pub fn init_render_poses_from_reader<B: Backend, R: io::Read + io::Seek>(
+        &self,
+        reader: R,
+        device: &B::Device,
+    ) -> Result<Vec<SimpleNerfCameraRays>, DatasetError> {
+        let mut archive = ZipArchive::new(reader)?;
+
+        let poses = read_poses(
+            read_npz_array(&mut archive, "render_poses")?,
+            device,
+            &ParseMode::Strict,
+        )?;
+
+        // NOTE: Only the shape of the images is read, and the rays are
+        // built from a blank image of that shape
+        let [_, rows, columns, channels] = to_shape::<4>(
+            read_npz_array(&mut archive, &self.array_names.images)?.shape(),
+            &self.array_names.images,
+            "[N, H, W, C]",
+        )?
+        .dims;
+        let image = self.image_layout.arrange(Tensor::<B, 4>::zeros(
+            [1, rows, columns, channels],
+            device,
+        ))?;
+
+        let focals =
+            optional(read_npz_array(&mut archive, &self.array_names.focal))?
+                .map(read_focals)
+                .transpose()?;
+        let focals = match focals {
+            Some(focals) => Some(focals),
+            None => optional(read_npz_array(&mut archive, "camera_angle_x"))?
+                .map(|array| read_camera_angles(array, &image))
+                .transpose()?,
+        };
+        let intrinsics = optional(read_npz_array(&mut archive, "intrinsics"))?
+            .map(read_intrinsics)
+            .transpose()?;
+        let intrinsics = resolve_intrinsics(
+            focals.map(|focals| focals.into_iter().take(1).collect()),
+            intrinsics
+                .map(|intrinsics| intrinsics.into_iter().take(1).collect()),
+            &image,
+            &self.array_names.focal,
+        )?;
+
+        // NOTE: The rays are built one pose at a time,
+        // so only the rays of one pose are on the device at a time
+        let config = self
+            .clone()
+            .with_image_indices(None)
+            .with_parse_mode(ParseMode::Strict);
+        let mut rays = Vec::with_capacity(poses.dims()[0]);
+        for pose in poses.iter_dim(0) {
+            let view = config.assemble(
+                intrinsics.clone(),
+                image.clone(),
+                pose,
+                None,
+                None,
+                device,
+            )?;
+            rays.extend(view.inners.iter().zip(view.iter()).map(
+                |(inner, item)| SimpleNerfCameraRays {
+                    directions: item.directions,
+                    distances: item.distances,
+                    intervals: item.intervals,
+                    origins: inner.origins.clone(),
+                    positions: item.positions,
+                },
+            ));
+        }
+
+        Ok(rays)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::*;
+    use super::*;
+
+    type Backend = burn::backend::Wgpu;
+
+    #[test]
+    fn simple_nerf_dataset_render_poses() {
+        let device = Default::default();
+
+        let mut render_poses = identity_poses(3);
+        render_poses[12 + 7] = 1.0;
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 2, 4, 3], vec![0.5_f32; 24])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+            ("render_poses", npy_bytes(&[3, 3, 4], render_poses)),
+        ]);
+        let rays = SimpleNerfDatasetConfig::new(5, 2.0..6.0)
+            .init_render_poses_from_reader::<Backend, _>(
+                io::Cursor::new(archive),
+                &device,
+            );
+        assert!(rays.is_ok(), "Error: {}", rays.unwrap_err());
+
+        let rays = rays.unwrap();
+        assert_eq!(rays.len(), 3);
+        assert_eq!(rays[0].directions.shape.dims, [2, 4, 5, 3]);
+        assert_eq!(rays[0].distances.shape.dims, [2, 4, 5, 1]);
+        assert_eq!(rays[0].intervals.shape.dims, [2, 4, 5, 1]);
+        assert_eq!(rays[0].origins.shape.dims, [2, 4, 1, 3]);
+        assert_eq!(rays[0].positions.shape.dims, [2, 4, 5, 3]);
+        assert_eq!(rays[0].origins.value[0..3], [0.0, 0.0, 4.0]);
+        assert_eq!(rays[1].origins.value[0..3], [0.0, 1.0, 4.0]);
+
+        let archive = npz_bytes(&[
+            ("focal", npy_bytes(&[], vec![2.0_f32])),
+            ("images", npy_bytes(&[1, 2, 4, 3], vec![0.5_f32; 24])),
+            ("poses", npy_bytes(&[1, 3, 4], identity_poses(1))),
+        ]);
+        let rays = SimpleNerfDatasetConfig::new(5, 2.0..6.0)
+            .init_render_poses_from_reader::<Backend, _>(
+                io::Cursor::new(archive),
+                &device,
+            );
+        assert!(matches!(
+            rays.unwrap_err(),
+            DatasetError::MissingArray { name, .. } if name == "render_poses"
+        ));
+    }
+}
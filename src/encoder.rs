@@ -5,11 +5,14 @@ use std::f32::consts::PI;
 #[derive(Config, Debug)]
 pub struct PositionalEncoderConfig {
     pub encoding_factor: usize,
+    #[config(default = true)]
+    pub include_input: bool,
 }
 
 #[derive(Debug, Module)]
 pub struct PositionalEncoder<B: Backend> {
     freqs: Tensor<B, 3>,
+    include_input: bool,
     phases: Tensor<B, 3>,
 }
 
@@ -38,6 +41,7 @@ impl PositionalEncoderConfig {
 
         Ok(PositionalEncoder {
             freqs: freqs.clone(),
+            include_input: self.include_input,
             phases: phases.clone(),
         })
     }
@@ -46,30 +50,29 @@ impl PositionalEncoderConfig {
         &self,
         input_size: usize,
     ) -> usize {
-        input_size * (2 * self.encoding_factor + 1)
+        input_size * (2 * self.encoding_factor + self.include_input as usize)
     }
 }
 
 impl<B: Backend> PositionalEncoder<B> {
-    pub fn forward(
+    // NOTE: Only the last dimension of coordinates is encoded
+    pub fn forward<const D: usize>(
         &self,
-        coordinates: Tensor<B, 2>,
-    ) -> Tensor<B, 2> {
-        let coordinates = coordinates.unsqueeze_dim::<3>(1);
-        let features = {
-            let shape = [coordinates.dims()[0] as i32, -1];
-            Tensor::cat(
-                vec![
-                    coordinates.clone(),
-                    (coordinates * self.freqs.clone() + self.phases.clone())
-                        .sin(),
-                ],
-                1,
-            )
-            .reshape(shape)
-        };
-
-        features
+        coordinates: Tensor<B, D>,
+    ) -> Tensor<B, D> {
+        let dims = coordinates.dims();
+        let coordinates =
+            coordinates.reshape([-1, dims[D - 1] as i32]).unsqueeze_dim::<3>(1);
+        let mut shape = dims.map(|dim| dim as i32);
+        shape[D - 1] = -1;
+        let encodings = (coordinates.clone() * self.freqs.clone()
+            + self.phases.clone())
+        .sin();
+        if self.include_input {
+            Tensor::cat(vec![coordinates, encodings], 1).reshape(shape)
+        } else {
+            encodings.reshape(shape)
+        }
     }
 }
 
@@ -85,6 +88,7 @@ mod tests {
 
         let config = PositionalEncoderConfig {
             encoding_factor: 10,
+            include_input: true,
         };
         let model = config.init::<Backend>(&device);
         assert!(model.is_ok(), "Error: {}", model.unwrap_err());
@@ -96,6 +100,7 @@ mod tests {
 
         let config = PositionalEncoderConfig {
             encoding_factor: 4,
+            include_input: true,
         };
         let model = config.init::<Backend>(&device);
         assert!(model.is_ok(), "Error: {}", model.unwrap_err());
@@ -107,8 +112,38 @@ mod tests {
 
         let config_invalid = PositionalEncoderConfig {
             encoding_factor: 0,
+            include_input: true,
         };
         let model = config_invalid.init::<Backend>(&device);
         assert!(model.is_err());
     }
+
+    #[test]
+    fn positional_encoder_input_inclusion() {
+        let device = Default::default();
+
+        let config = PositionalEncoderConfig::new(4);
+        let model = config.init::<Backend>(&device).unwrap();
+        let input = Tensor::<Backend, 4>::random(
+            [2, 5, 7, 3],
+            burn::tensor::Distribution::Default,
+            &device,
+        );
+        let output = model.forward(input.clone());
+        assert_eq!(output.dims(), [2, 5, 7, config.get_output_size(3)]);
+        assert_eq!(output.dims()[3], 3 * (2 * 4 + 1));
+
+        let config_excluded = config.with_include_input(false);
+        let model_excluded = config_excluded.init::<Backend>(&device).unwrap();
+        let output_excluded = model_excluded.forward(input);
+        assert_eq!(
+            output_excluded.dims(),
+            [2, 5, 7, config_excluded.get_output_size(3)]
+        );
+        assert_eq!(output_excluded.dims()[3], 3 * 2 * 4);
+        assert_eq!(
+            output_excluded.into_data().value,
+            output.slice([0..2, 0..5, 0..7, 3..27]).into_data().value
+        );
+    }
 }
@@ -1,10 +1,9 @@
 use crate::*;
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use burn::{
     data::dataset::Dataset, prelude::*, tensor::backend::AutodiffBackend,
 };
-use image::{ImageFormat, RgbImage};
 use std::{path::PathBuf, time};
 
 #[derive(Clone, Debug)]
@@ -91,27 +90,14 @@ impl<B: AutodiffBackend> Tester<B> {
             fps: fps_rendering,
         };
         eval_output
-            .save(&self.artifact_directory.join("evaluation-output.json"))?;
+            .save(self.artifact_directory.join("evaluation-output.json"))?;
 
         let collage_path = self.artifact_directory.join("collage.png");
-        let collage = {
-            let image = Tensor::cat(
-                vec![
-                    Tensor::cat(input_images, 0),
-                    Tensor::cat(output_images, 0),
-                ],
-                1,
-            );
-            let [height, width, ..] = image.dims();
-            let image = (image.clamp(0.0, 1.0) * 255.0)
-                .into_data()
-                .convert::<u8>()
-                .value;
-
-            RgbImage::from_vec(width as u32, height as u32, image)
-                .ok_or(anyhow!("Collage buffer is too small"))?
-        };
-        collage.save_with_format(&collage_path, ImageFormat::Png)?;
+        let collage = Tensor::cat(
+            vec![Tensor::cat(input_images, 0), Tensor::cat(output_images, 0)],
+            1,
+        );
+        io::save_image(collage, &collage_path)?;
         eprintln!("Collage is saved at {:?}", collage_path);
 
         Ok(TestOutput {
@@ -0,0 +1,56 @@
+use anyhow::{bail, Result};
+use burn::prelude::*;
+use image::{ImageFormat, RgbImage};
+use std::path::Path;
+
+// NOTE: The image of [H, W, 3] is clamped to [0, 1] and rounded to 8 bits
+pub fn save_image<B: Backend>(
+    image: Tensor<B, 3>,
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    let [height, width, channel_count] = image.dims();
+    if channel_count != 3 {
+        bail!("Image has {} channels instead of 3", channel_count);
+    }
+
+    let values =
+        (image.clamp(0.0, 1.0) * 255.0 + 0.5).into_data().convert::<u8>().value;
+    let Some(image) = RgbImage::from_vec(width as u32, height as u32, values)
+    else {
+        bail!("Image buffer is too small");
+    };
+    image.save_with_format(path, ImageFormat::Png)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    type Backend = burn::backend::Wgpu;
+
+    #[test]
+    fn save_image_round_trip() {
+        let device = Default::default();
+        let path = env::temp_dir().join("simple-nerf-save-image.png");
+
+        let values =
+            (0..4 * 5 * 3).map(|index| index as f32 / 59.0).collect::<Vec<_>>();
+        let image =
+            Tensor::<Backend, 1>::from_floats(values.as_slice(), &device)
+                .reshape([4, 5, 3]);
+        let result = save_image(image, &path);
+        assert!(result.is_ok(), "Error: {}", result.unwrap_err());
+
+        let loaded = image::open(&path).unwrap().to_rgb8();
+        assert_eq!(loaded.dimensions(), (5, 4));
+        for (value, loaded) in values.iter().zip(loaded.into_raw()) {
+            assert!((value - loaded as f32 / 255.0).abs() <= 1.0 / 255.0);
+        }
+
+        let image = Tensor::<Backend, 3>::zeros([4, 5, 4], &device);
+        assert!(save_image(image, &path).is_err());
+    }
+}
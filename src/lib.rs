@@ -1,18 +1,33 @@
 extern crate anyhow;
 extern crate burn;
+extern crate flate2;
 extern crate kdam;
 extern crate image;
+extern crate log;
 extern crate npyz;
+extern crate rand;
+extern crate rayon;
 extern crate regex;
 extern crate reqwest;
+extern crate rmp_serde;
+extern crate serde;
+extern crate serde_json;
+extern crate sha2;
+extern crate tar;
+#[cfg(feature = "async")]
+extern crate tokio;
 extern crate zip;
 
 pub mod dataset;
 pub mod encoder;
 pub mod experiment;
+pub mod io;
 pub mod metric;
+pub mod model;
+pub mod render;
 pub mod renderer;
 pub mod scene;
+pub mod train;
 
 pub mod prelude {
     pub use crate::*;
@@ -34,6 +34,55 @@ impl<B: Backend> PsnrMetric<B> {
     }
 }
 
+// NOTE: Identical images have the infinite PSNR
+pub fn psnr<B: Backend>(
+    predicted: Tensor<B, 3>,
+    target: Tensor<B, 3>,
+) -> f32 {
+    let error = predicted - target;
+    psnr_from_mse((error.clone() * error).mean().into_scalar().elem())
+}
+
+pub fn psnr_from_mse(mse: f32) -> f32 {
+    if mse == 0.0 {
+        f32::INFINITY
+    } else {
+        -10.0 * mse.log10()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PsnrAggregator {
+    count: usize,
+    sum: f64,
+}
+
+impl PsnrAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update<B: Backend>(
+        &mut self,
+        predicted: Tensor<B, 3>,
+        target: Tensor<B, 3>,
+    ) -> f32 {
+        let psnr = psnr(predicted, target);
+        self.count += 1;
+        self.sum += psnr as f64;
+        psnr
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    // NOTE: It is None if no images have been aggregated
+    pub fn mean(&self) -> Option<f32> {
+        (self.count > 0).then(|| (self.sum / self.count as f64) as f32)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -61,4 +110,26 @@ mod tests {
         let psnr = metric.forward(logits, targets);
         assert!(psnr.equal(psnr_true).all().into_scalar());
     }
+
+    #[test]
+    fn psnr_of_images() {
+        let device = Default::default();
+
+        let image = Tensor::<Backend, 3>::from_floats(
+            [[[0.1, 0.2, 0.3], [0.4, 0.5, 0.6]]],
+            &device,
+        );
+        assert_eq!(psnr(image.clone(), image.clone()), f32::INFINITY);
+
+        // NOTE: The constant error of 0.1 gives the MSE of 0.01 and PSNR of 20
+        let psnr_constant = psnr(image.clone() + 0.1, image.clone());
+        assert!((psnr_constant - 20.0).abs() < 1e-3);
+
+        let mut aggregator = PsnrAggregator::new();
+        assert_eq!(aggregator.mean(), None);
+        aggregator.update(image.clone() + 0.1, image.clone());
+        aggregator.update(image.clone() - 0.01, image);
+        assert_eq!(aggregator.count(), 2);
+        assert!((aggregator.mean().unwrap() - 30.0).abs() < 1e-3);
+    }
 }
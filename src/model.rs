@@ -0,0 +1,256 @@
+use crate::*;
+use anyhow::Result;
+use burn::{
+    prelude::*,
+    record::{FullPrecisionSettings, NamedMpkFileRecorder, Recorder},
+};
+use std::{io, path::Path};
+
+#[derive(Config, Debug, PartialEq)]
+pub struct SimpleNerfModelConfig {
+    pub hidden_size: usize,
+    pub num_layers: usize,
+    pub num_pos_freqs: usize,
+    pub num_dir_freqs: usize,
+}
+
+// NOTE: The model is a volumetric scene which has a view-dependent head
+#[derive(Debug, Module)]
+pub struct SimpleNerfModel<B: Backend> {
+    hidden_size: usize,
+    num_dir_freqs: usize,
+    num_layers: usize,
+    num_pos_freqs: usize,
+    scene: scene::VolumetricScene<B>,
+}
+
+impl SimpleNerfModelConfig {
+    pub fn init<B: Backend>(
+        &self,
+        device: &B::Device,
+    ) -> Result<SimpleNerfModel<B>> {
+        // NOTE: The encoded positions are concatenated again at the middle layer
+        let scene = scene::VolumetricSceneConfig::new(
+            self.hidden_size,
+            encoder::PositionalEncoderConfig::new(self.num_pos_freqs),
+        )
+        .with_direction_encoder(Some(encoder::PositionalEncoderConfig::new(
+            self.num_dir_freqs,
+        )))
+        .with_layer_count(self.num_layers)
+        .with_skip_index(self.num_layers / 2)
+        .init(device)?;
+
+        Ok(SimpleNerfModel {
+            hidden_size: self.hidden_size,
+            num_dir_freqs: self.num_dir_freqs,
+            num_layers: self.num_layers,
+            num_pos_freqs: self.num_pos_freqs,
+            scene,
+        })
+    }
+
+    // NOTE: The weights are loaded from "{path}.mpk" after the config
+    // in "{path}.json" is checked against this config
+    pub fn load_file<B: Backend>(
+        &self,
+        path: impl AsRef<Path>,
+        device: &B::Device,
+    ) -> io::Result<SimpleNerfModel<B>> {
+        let path = path.as_ref();
+
+        let config = SimpleNerfModelConfig::load(path.with_extension("json"))
+            .map_err(|error| {
+            io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+        })?;
+        if config != *self {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Checkpoint config does not match: {} instead of {}",
+                    config, self
+                ),
+            ));
+        }
+
+        let model = self.init::<B>(device).map_err(|error| {
+            io::Error::new(io::ErrorKind::InvalidInput, error)
+        })?;
+        let record = NamedMpkFileRecorder::<FullPrecisionSettings>::new()
+            .load::<SimpleNerfModelRecord<B>>(
+                path.with_extension("mpk"),
+                device,
+            )
+            .map_err(|error| {
+                io::Error::new(io::ErrorKind::InvalidData, error)
+            })?;
+
+        let hidden_size = self.hidden_size;
+        if record.scene.hidden_layers.len() != self.num_layers
+            || record.scene.output_layer.weight.dims() != [hidden_size, 1]
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Checkpoint weights do not match its config",
+            ));
+        }
+
+        Ok(model.load_record(record))
+    }
+}
+
+impl<B: Backend> SimpleNerfModel<B> {
+    pub fn config(&self) -> SimpleNerfModelConfig {
+        SimpleNerfModelConfig::new(
+            self.hidden_size,
+            self.num_layers,
+            self.num_pos_freqs,
+            self.num_dir_freqs,
+        )
+    }
+
+    // NOTE: The config is saved to "{path}.json" and the weights to "{path}.mpk".
+    // It takes a reference, so on a model value `Module::save_file` is picked
+    pub fn save_file(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> io::Result<()> {
+        let path = path.as_ref();
+
+        self.config().save(path.with_extension("json"))?;
+        NamedMpkFileRecorder::<FullPrecisionSettings>::new()
+            .record(self.clone().into_record(), path.with_extension("mpk"))
+            .map_err(io::Error::other)
+    }
+
+    // NOTE: The outputs are densities of [..., 1] and colors of [..., 3]
+    pub fn forward<const D: usize>(
+        &self,
+        positions: Tensor<B, D>,
+        directions: Tensor<B, D>,
+    ) -> (Tensor<B, D>, Tensor<B, D>) {
+        let shape = positions.dims();
+        let outputs = self
+            .scene
+            .forward(directions.reshape([-1, 3]), positions.reshape([-1, 3]));
+        let size = outputs.dims()[0];
+
+        let mut densities_shape = shape;
+        densities_shape[D - 1] = 1;
+        let densities = outputs
+            .clone()
+            .slice([0..size, 3..4])
+            .reshape(Shape::new(densities_shape));
+        let colors = outputs.slice([0..size, 0..3]).reshape(Shape::new(shape));
+
+        (densities, colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use burn::{
+        backend::Autodiff, module::AutodiffModule, optim::AdamConfig,
+        tensor::Distribution, train::TrainStep,
+    };
+    use std::env;
+
+    type Backend = burn::backend::Wgpu;
+
+    #[test]
+    fn simple_nerf_model_output_shape() {
+        let device = Default::default();
+
+        let model =
+            SimpleNerfModelConfig::new(16, 4, 6, 2).init::<Backend>(&device);
+        assert!(model.is_ok(), "Error: {}", model.unwrap_err());
+
+        let model = model.unwrap();
+        let positions =
+            Tensor::random([2, 5, 7, 3], Distribution::Default, &device);
+        let directions = positions.random_like(Distribution::Default);
+
+        let (densities, colors) = model.forward(positions, directions);
+        assert_eq!(densities.dims(), [2, 5, 7, 1]);
+        assert_eq!(colors.dims(), [2, 5, 7, 3]);
+        assert!(colors
+            .into_data()
+            .value
+            .iter()
+            .all(|color| (0.0..=1.0).contains(color)));
+        assert!(densities
+            .into_data()
+            .value
+            .iter()
+            .all(|density| *density >= 0.0));
+
+        let model =
+            SimpleNerfModelConfig::new(16, 0, 6, 2).init::<Backend>(&device);
+        assert!(model.is_err());
+    }
+
+    #[test]
+    fn simple_nerf_model_checkpoint() {
+        let device = Default::default();
+
+        let config = train::SimpleNerfTrainingConfig::new(
+            SimpleNerfModelConfig::new(16, 4, 6, 2),
+        );
+        let step = config.init::<Autodiff<Backend>>(&device).unwrap();
+        let positions = Tensor::<Autodiff<Backend>, 5>::random(
+            [1, 2, 3, 4, 3],
+            Distribution::Default,
+            &device,
+        );
+        let batch = dataset::SimpleNerfBatch {
+            depths: None,
+            directions: positions.random_like(Distribution::Default),
+            distances: Tensor::<Autodiff<Backend>, 1, Int>::arange(
+                2..6,
+                &device,
+            )
+            .float()
+            .reshape([1, 1, 1, 4, 1])
+            .repeat(1, 2)
+            .repeat(2, 3),
+            images: Tensor::random(
+                [1, 2, 3, 3],
+                Distribution::Default,
+                &device,
+            ),
+            intervals: Tensor::ones([1, 2, 3, 4, 1], &device),
+            masks: None,
+            positions,
+        };
+        let output = TrainStep::step(&step, batch);
+        let mut optimizer = AdamConfig::new().init();
+        let model = step
+            .optimize(&mut optimizer, config.learning_rate, output.grads)
+            .model()
+            .valid();
+
+        let path = env::temp_dir().join("simple-nerf-model-checkpoint");
+        let result = SimpleNerfModel::save_file(&model, &path);
+        assert!(result.is_ok(), "Error: {}", result.unwrap_err());
+
+        let loaded = config.model.load_file::<Backend>(&path, &device);
+        assert!(loaded.is_ok(), "Error: {}", loaded.unwrap_err());
+
+        let loaded = loaded.unwrap();
+        let positions = Tensor::<Backend, 4>::random(
+            [5, 7, 4, 3],
+            Distribution::Default,
+            &device,
+        );
+        let directions = positions.random_like(Distribution::Default);
+        let outputs = model.forward(positions.clone(), directions.clone());
+        let loaded_outputs = loaded.forward(positions, directions);
+        assert_eq!(outputs.0.into_data(), loaded_outputs.0.into_data());
+        assert_eq!(outputs.1.into_data(), loaded_outputs.1.into_data());
+
+        let loaded = SimpleNerfModelConfig::new(32, 4, 6, 2)
+            .load_file::<Backend>(&path, &device);
+        assert_eq!(loaded.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,826 @@
+use crate::*;
+use burn::{data::dataset::Dataset, prelude::*};
+use rand::Rng;
+use std::ops::Range;
+
+// NOTE: The last sample on each ray extends to the infinity
+const LAST_DELTA: f32 = 1e10;
+
+const RAY_CHUNK_SIZE: usize = 4096;
+
+#[derive(Clone, Debug)]
+pub struct RenderOutput<B: Backend> {
+    pub depth: Tensor<B, 3>,
+    pub rgb: Tensor<B, 3>,
+    pub weights: Tensor<B, 4>,
+}
+
+// NOTE: The inputs are densities of [H, W, P, 1], colors of [H, W, P, 3]
+// and distances of [H, W, P, 1]
+pub fn volume_render<B: Backend>(
+    densities: Tensor<B, 4>,
+    colors: Tensor<B, 4>,
+    distances: Tensor<B, 4>,
+) -> RenderOutput<B> {
+    let [height, width, points_per_ray, _] = distances.dims();
+    let device = distances.device();
+
+    let deltas = {
+        let lasts = Tensor::full([height, width, 1, 1], LAST_DELTA, &device);
+        if points_per_ray > 1 {
+            let starts = distances.clone().slice([
+                0..height,
+                0..width,
+                0..points_per_ray - 1,
+            ]);
+            let ends = distances.clone().slice([
+                0..height,
+                0..width,
+                1..points_per_ray,
+            ]);
+            Tensor::cat(vec![ends - starts, lasts], 2)
+        } else {
+            lasts
+        }
+    };
+
+    let weights = volume_weights(densities, deltas);
+
+    RenderOutput {
+        depth: (weights.clone() * distances).sum_dim(2).squeeze::<3>(2),
+        rgb: (weights.clone() * colors).sum_dim(2).squeeze::<3>(2),
+        weights,
+    }
+}
+
+// NOTE: The inputs are densities of [H, W, P, 1] and intervals of [H, W, P, 1],
+// and the weights of [H, W, P, 1] are for compositing any sample values
+pub fn volume_weights<B: Backend>(
+    densities: Tensor<B, 4>,
+    intervals: Tensor<B, 4>,
+) -> Tensor<B, 4> {
+    let [height, width, points_per_ray, _] = intervals.dims();
+    let device = intervals.device();
+
+    let optical_depths = densities * intervals;
+    let alphas = -(-optical_depths.clone()).exp() + 1.0;
+
+    // NOTE: The transmittance of a sample only counts the samples before it
+    let transmittances = {
+        let mut accumulated = Tensor::zeros([height, width, 1, 1], &device);
+        let mut transmittances = Vec::with_capacity(points_per_ray);
+        for index in 0..points_per_ray {
+            transmittances.push((-accumulated.clone()).exp());
+            accumulated = accumulated
+                + optical_depths.clone().slice([
+                    0..height,
+                    0..width,
+                    index..index + 1,
+                ]);
+        }
+        Tensor::cat(transmittances, 2)
+    };
+
+    alphas * transmittances
+}
+
+// NOTE: The rays not fully saturated show the background, which should be
+// the same as the one composited into the dataset images
+pub fn volume_render_with_bg<B: Backend>(
+    densities: Tensor<B, 4>,
+    colors: Tensor<B, 4>,
+    distances: Tensor<B, 4>,
+    background: [f32; 3],
+) -> RenderOutput<B> {
+    let output = volume_render(densities, colors, distances);
+    RenderOutput {
+        rgb: output.composite(background),
+        ..output
+    }
+}
+
+impl<B: Backend> RenderOutput<B> {
+    // NOTE: The background fills in what the weights leave transparent
+    pub fn composite(
+        &self,
+        background: [f32; 3],
+    ) -> Tensor<B, 3> {
+        let device = self.rgb.device();
+        let opacities = self.weights.clone().sum_dim(2).squeeze::<3>(2);
+        let background =
+            Tensor::<B, 1>::from_floats(background, &device).reshape([1, 1, 3]);
+
+        self.rgb.clone() + (-opacities + 1.0) * background
+    }
+}
+
+// NOTE: The rays are cast from the pose of [3, 4] or [4, 4] in the same way
+// as the dataset images, but the image selection, crop and resizing of the
+// config are ignored, so the output is always of [height, width, 3]
+pub fn render_view<B: Backend>(
+    model: &model::SimpleNerfModel<B>,
+    pose: Tensor<B, 2>,
+    focal: f32,
+    height: usize,
+    width: usize,
+    config: &dataset::SimpleNerfDatasetConfig,
+    device: &B::Device,
+) -> Result<Tensor<B, 3>, dataset::DatasetError> {
+    let view = config
+        .clone()
+        .with_crop(None)
+        .with_downscale(1)
+        .with_image_indices(None)
+        .with_parse_mode(dataset::ParseMode::Strict)
+        .with_resize_to(None)
+        .init_from_tensors(
+            Tensor::zeros([1, height, width, 3], device),
+            pose.unsqueeze_dim(0),
+            focal,
+            device,
+        )?;
+    let input = view
+        .get(0)
+        .ok_or_else(|| dataset::DatasetError::MissingArray {
+            name: "poses".into(),
+            available: vec![],
+        })?
+        .into_input::<B>(device);
+
+    let output = ChunkedRenderer::new(RAY_CHUNK_SIZE).render(
+        model,
+        input.positions,
+        input.directions,
+        input.distances,
+    );
+
+    Ok(match config.background {
+        Some(background) => output.composite(background),
+        None => output.rgb,
+    })
+}
+
+#[derive(Clone, Debug)]
+pub struct ChunkedRenderer {
+    chunk_size: usize,
+}
+
+impl ChunkedRenderer {
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            chunk_size: chunk_size.max(1),
+        }
+    }
+
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    // NOTE: The rays are flattened in row-major order and evaluated
+    // by chunks, so at most `chunk_size * P` samples are in the model at once
+    pub fn render<B: Backend>(
+        &self,
+        model: &model::SimpleNerfModel<B>,
+        positions: Tensor<B, 4>,
+        directions: Tensor<B, 4>,
+        distances: Tensor<B, 4>,
+    ) -> RenderOutput<B> {
+        let [height, width, points_per_ray, _] = positions.dims();
+        let ray_count = height * width;
+
+        let flatten = |tensor: Tensor<B, 4>| {
+            let size = tensor.dims()[3];
+            tensor.reshape([ray_count, 1, points_per_ray, size])
+        };
+        let positions = flatten(positions);
+        let directions = flatten(directions);
+        let distances = flatten(distances);
+
+        let mut outputs = vec![];
+        for start in (0..ray_count).step_by(self.chunk_size) {
+            let end = (start + self.chunk_size).min(ray_count);
+            let chunk = |tensor: Tensor<B, 4>| {
+                let size = tensor.dims()[3];
+                tensor.slice([start..end, 0..1, 0..points_per_ray, 0..size])
+            };
+            let (densities, colors) = model
+                .forward(chunk(positions.clone()), chunk(directions.clone()));
+            outputs.push(volume_render(
+                densities,
+                colors,
+                chunk(distances.clone()),
+            ));
+        }
+
+        let (depths, (rgbs, weights)): (Vec<_>, (Vec<_>, Vec<_>)) = outputs
+            .into_iter()
+            .map(|output| (output.depth, (output.rgb, output.weights)))
+            .unzip();
+        RenderOutput {
+            depth: Tensor::cat(depths, 0).reshape([height, width, 1]),
+            rgb: Tensor::cat(rgbs, 0).reshape([height, width, 3]),
+            weights: Tensor::cat(weights, 0).reshape([
+                height,
+                width,
+                points_per_ray,
+                1,
+            ]),
+        }
+    }
+}
+
+// NOTE: The predictions and targets are of the same shape, e.g. [H * W, 3]
+// or [H, W, 3], and the mask of [..., 1] weights the pixels in the mean
+pub fn mse_loss<B: Backend, const D: usize>(
+    predicted: Tensor<B, D>,
+    target: Tensor<B, D>,
+    mask: Option<Tensor<B, D>>,
+) -> Tensor<B, 1> {
+    let error = predicted - target;
+    masked_mean(error.clone() * error, mask)
+}
+
+pub fn l1_loss<B: Backend, const D: usize>(
+    predicted: Tensor<B, D>,
+    target: Tensor<B, D>,
+    mask: Option<Tensor<B, D>>,
+) -> Tensor<B, 1> {
+    masked_mean((predicted - target).abs(), mask)
+}
+
+// NOTE: The mean is zero if the mask covers nothing
+fn masked_mean<B: Backend, const D: usize>(
+    values: Tensor<B, D>,
+    mask: Option<Tensor<B, D>>,
+) -> Tensor<B, 1> {
+    match mask {
+        Some(mask) => {
+            let weights = mask.expand(values.shape());
+            (values * weights.clone()).sum()
+                / weights.sum().clamp_min(f32::EPSILON)
+        },
+        None => values.mean(),
+    }
+}
+
+// NOTE: The bins of [H, W, P, 1] are the coarse distances, and each interval
+// between the consecutive bins is weighted by the coarse weight at its start.
+// The fine distances of [H, W, F, 1] are drawn at the evenly spaced quantiles,
+// and the weights summing to zero fall back to the uniform distribution
+pub fn sample_pdf<B: Backend>(
+    bins: Tensor<B, 4>,
+    weights: Tensor<B, 4>,
+    num_fine: usize,
+) -> Tensor<B, 4> {
+    let [height, width, _, _] = bins.dims();
+    let quantiles =
+        ((Tensor::<B, 1, Int>::arange(0..num_fine as i64, &bins.device())
+            .float()
+            + 0.5)
+            / num_fine as f32)
+            .reshape([1, 1, num_fine])
+            .expand([height, width, num_fine]);
+
+    invert_cdf(bins, weights, quantiles)
+}
+
+// NOTE: The quantiles are drawn uniformly and sorted on each ray,
+// so the fine distances of [H, W, F, 1] are sorted as well
+pub fn resample_distances<B: Backend>(
+    coarse_distances: Tensor<B, 4>,
+    weights: Tensor<B, 4>,
+    num_fine: usize,
+    rng: &mut impl Rng,
+) -> Tensor<B, 4> {
+    let [height, width, _, _] = coarse_distances.dims();
+    let mut quantiles = (0..height * width * num_fine)
+        .map(|_| rng.gen::<f32>())
+        .collect::<Vec<_>>();
+    for ray in quantiles.chunks_exact_mut(num_fine.max(1)) {
+        ray.sort_by(f32::total_cmp);
+    }
+    let quantiles = Tensor::<B, 3>::from_data(
+        Data::<f32, 3>::new(quantiles, Shape::new([height, width, num_fine]))
+            .convert(),
+        &coarse_distances.device(),
+    );
+
+    invert_cdf(coarse_distances, weights, quantiles)
+}
+
+// NOTE: The quantiles are of [H, W, F] in [0, 1]
+fn invert_cdf<B: Backend>(
+    bins: Tensor<B, 4>,
+    weights: Tensor<B, 4>,
+    quantiles: Tensor<B, 3>,
+) -> Tensor<B, 4> {
+    let [height, width, points_per_ray, _] = bins.dims();
+    let num_fine = quantiles.dims()[2];
+    let device = bins.device();
+    if points_per_ray < 2 {
+        return bins.repeat(2, num_fine);
+    }
+    let interval_count = points_per_ray - 1;
+
+    let bins = bins.reshape([height, width, points_per_ray]);
+    let weights = weights.reshape([height, width, points_per_ray]).slice([
+        0..height,
+        0..width,
+        0..interval_count,
+    ]) + 1e-5;
+    let probabilities = weights.clone() / weights.sum_dim(2);
+
+    let cumulative_probabilities = {
+        let mut accumulated = Tensor::zeros([height, width, 1], &device);
+        let mut cumulative_probabilities = vec![accumulated.clone()];
+        for index in 0..interval_count {
+            accumulated = accumulated
+                + probabilities.clone().slice([
+                    0..height,
+                    0..width,
+                    index..index + 1,
+                ]);
+            cumulative_probabilities.push(accumulated.clone());
+        }
+        Tensor::cat(cumulative_probabilities, 2)
+    };
+
+    // NOTE: Each quantile falls in the last interval starting below it
+    let lower_indices =
+        cumulative_probabilities
+            .clone()
+            .unsqueeze_dim::<4>(2)
+            .expand([height, width, num_fine, points_per_ray])
+            .lower_equal(
+                quantiles
+                    .clone()
+                    .reshape([height, width, num_fine, 1])
+                    .expand([height, width, num_fine, points_per_ray]),
+            )
+            .int()
+            .sum_dim(3)
+            .reshape([height, width, num_fine])
+            .sub_scalar(1)
+            .clamp(0, interval_count as i64 - 1);
+    let upper_indices = lower_indices.clone().add_scalar(1);
+
+    let lower_probabilities =
+        cumulative_probabilities.clone().gather(2, lower_indices.clone());
+    let upper_probabilities =
+        cumulative_probabilities.gather(2, upper_indices.clone());
+    let lower_bins = bins.clone().gather(2, lower_indices);
+    let upper_bins = bins.gather(2, upper_indices);
+
+    let fractions = ((quantiles - lower_probabilities.clone())
+        / (upper_probabilities - lower_probabilities))
+        .clamp(0.0, 1.0);
+
+    (lower_bins.clone() + fractions * (upper_bins - lower_bins))
+        .reshape([height, width, num_fine, 1])
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DepthColormap {
+    Grayscale,
+    Turbo,
+    Viridis,
+}
+
+impl DepthColormap {
+    // NOTE: The colormaps are approximated by a few evenly spaced colors
+    fn colors(&self) -> &'static [[f32; 3]] {
+        match self {
+            DepthColormap::Grayscale => &[[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]],
+            DepthColormap::Turbo => &[
+                [0.190, 0.072, 0.232],
+                [0.275, 0.447, 0.906],
+                [0.157, 0.737, 0.922],
+                [0.275, 0.929, 0.525],
+                [0.643, 0.988, 0.235],
+                [0.937, 0.812, 0.227],
+                [0.984, 0.494, 0.125],
+                [0.839, 0.208, 0.020],
+                [0.480, 0.016, 0.011],
+            ],
+            DepthColormap::Viridis => &[
+                [0.267, 0.005, 0.329],
+                [0.231, 0.322, 0.545],
+                [0.129, 0.569, 0.549],
+                [0.369, 0.788, 0.384],
+                [0.992, 0.906, 0.145],
+            ],
+        }
+    }
+}
+
+// NOTE: The depth of [H, W] is normalized from the range to a grayscale image
+// of [H, W, 3], and the depths out of the range or NaN are black
+pub fn depth_to_image<B: Backend>(
+    depth: Tensor<B, 2>,
+    range: Range<f32>,
+) -> Tensor<B, 3> {
+    depth_to_colormap_image(depth, range, DepthColormap::Grayscale)
+}
+
+pub fn depth_to_colormap_image<B: Backend>(
+    depth: Tensor<B, 2>,
+    range: Range<f32>,
+    colormap: DepthColormap,
+) -> Tensor<B, 3> {
+    let [height, width] = depth.dims();
+    let device = depth.device();
+
+    // NOTE: The comparisons with NaN are false, so NaN is out of the range
+    let validities = depth.clone().greater_equal_elem(range.start).int()
+        * depth.clone().lower_equal_elem(range.end).int();
+    let ratios = ((depth - range.start) / (range.end - range.start).max(1e-6))
+        .clamp(0.0, 1.0)
+        .mask_fill(validities.clone().equal_elem(0), 0.0)
+        .reshape([height, width, 1]);
+
+    // NOTE: Each segment between the consecutive colors is interpolated
+    let colors = colormap.colors();
+    let segment_count = (colors.len() - 1) as f32;
+    let color = |index: usize| {
+        Tensor::<B, 1>::from_floats(colors[index], &device).reshape([1, 1, 3])
+    };
+    let mut image = color(0).repeat(0, height).repeat(1, width);
+    for index in 1..colors.len() {
+        let progress = (ratios.clone() * segment_count - (index - 1) as f32)
+            .clamp(0.0, 1.0);
+        image = image + progress * (color(index) - color(index - 1));
+    }
+
+    image * validities.float().reshape([height, width, 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use burn::{
+        backend::Autodiff, module::AutodiffModule, optim::AdamConfig,
+        tensor::Distribution, train::TrainStep,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    type Backend = burn::backend::Wgpu;
+
+    #[test]
+    fn volume_render_opaque_sample() {
+        let device = Default::default();
+
+        let colors = Tensor::<Backend, 1>::from_floats(
+            [0.2, 0.4, 0.6, 0.9, 0.1, 0.5, 0.3, 0.7, 0.8],
+            &device,
+        )
+        .reshape([1, 1, 3, 3]);
+        let distances =
+            Tensor::<Backend, 1>::from_floats([2.0, 3.0, 4.0], &device)
+                .reshape([1, 1, 3, 1]);
+
+        let densities =
+            Tensor::<Backend, 1>::from_floats([0.0, 1e10, 0.0], &device)
+                .reshape([1, 1, 3, 1]);
+        let output =
+            volume_render(densities, colors.clone(), distances.clone());
+        assert_eq!(output.rgb.dims(), [1, 1, 3]);
+        assert_eq!(output.depth.dims(), [1, 1, 1]);
+        assert_eq!(output.weights.dims(), [1, 1, 3, 1]);
+        assert_eq!(output.rgb.into_data().value, [0.9, 0.1, 0.5]);
+        assert_eq!(output.depth.into_data().value, [3.0]);
+        assert_eq!(output.weights.into_data().value, [0.0, 1.0, 0.0]);
+
+        let densities = Tensor::<Backend, 1>::from_floats([1.0], &device)
+            .reshape([1, 1, 1, 1]);
+        let output = volume_render(
+            densities,
+            colors.slice([0..1, 0..1, 2..3]),
+            distances.slice([0..1, 0..1, 2..3]),
+        );
+        assert_eq!(output.rgb.into_data().value, [0.3, 0.7, 0.8]);
+    }
+
+    #[test]
+    fn volume_render_empty_space() {
+        let device = Default::default();
+
+        let densities = Tensor::<Backend, 4>::zeros([2, 2, 5, 1], &device);
+        let colors = Tensor::<Backend, 4>::ones([2, 2, 5, 3], &device);
+        let distances = Tensor::<Backend, 1, Int>::arange(2..7, &device)
+            .float()
+            .reshape([1, 1, 5, 1])
+            .repeat(0, 2)
+            .repeat(1, 2);
+
+        let output = volume_render(densities, colors, distances);
+        assert_eq!(output.rgb.clone().into_data().value, [0.0; 12]);
+        assert_eq!(
+            output.composite([1.0, 0.5, 0.0]).into_data().value,
+            [1.0, 0.5, 0.0].repeat(4)
+        );
+    }
+
+    #[test]
+    fn volume_render_transparent_ray_background() {
+        let device = Default::default();
+
+        let densities = Tensor::<Backend, 4>::zeros([1, 2, 4, 1], &device);
+        let colors = Tensor::<Backend, 4>::ones([1, 2, 4, 3], &device);
+        let distances = Tensor::<Backend, 1, Int>::arange(2..6, &device)
+            .float()
+            .reshape([1, 1, 4, 1])
+            .repeat(1, 2);
+
+        for background in [[0.0; 3], [1.0; 3], [0.25, 0.5, 0.75]] {
+            let output = volume_render_with_bg(
+                densities.clone(),
+                colors.clone(),
+                distances.clone(),
+                background,
+            );
+            assert_eq!(output.rgb.into_data().value, background.repeat(2));
+        }
+    }
+
+    #[test]
+    fn depth_to_image_normalization() {
+        let device = Default::default();
+
+        let image = |depth: f32, colormap: DepthColormap| {
+            depth_to_colormap_image(
+                Tensor::<Backend, 2>::full([2, 3], depth, &device),
+                2.0..6.0,
+                colormap,
+            )
+            .into_data()
+            .value
+        };
+
+        let near = image(2.0, DepthColormap::Grayscale);
+        assert_eq!(near.len(), 18);
+        assert!(near.iter().all(|value| value.abs() < 1e-6));
+        let far = image(6.0, DepthColormap::Grayscale);
+        assert!(far.iter().all(|value| (value - 1.0).abs() < 1e-6));
+        let middle = image(4.0, DepthColormap::Grayscale);
+        assert!(middle.iter().all(|value| (value - 0.5).abs() < 1e-6));
+
+        for depth in [f32::NAN, 1.0, 7.0] {
+            for colormap in [DepthColormap::Turbo, DepthColormap::Viridis] {
+                assert!(image(depth, colormap)
+                    .iter()
+                    .all(|value| *value == 0.0));
+            }
+        }
+        let near = image(2.0, DepthColormap::Viridis);
+        assert!((near[0] - 0.267).abs() < 1e-6);
+        let far = image(6.0, DepthColormap::Turbo);
+        assert!((far[0] - 0.480).abs() < 1e-6);
+
+        let depth = Tensor::<Backend, 1>::from_floats([2.0, 6.0], &device)
+            .reshape([1, 2]);
+        assert_eq!(
+            depth_to_image(depth, 2.0..6.0).into_data().value,
+            [0.0, 0.0, 0.0, 1.0, 1.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn losses_of_small_example() {
+        let device = Default::default();
+
+        let predicted = Tensor::<Backend, 2>::from_floats(
+            [[0.0, 0.5], [1.0, 1.0]],
+            &device,
+        );
+        let target = Tensor::<Backend, 2>::from_floats(
+            [[0.0, 0.0], [0.0, 1.0]],
+            &device,
+        );
+        let loss = mse_loss(predicted.clone(), target.clone(), None);
+        assert_eq!(loss.dims(), [1]);
+        assert!((loss.into_scalar() - 0.3125).abs() < 1e-6);
+        let loss = l1_loss(predicted, target, None);
+        assert!((loss.into_scalar() - 0.375).abs() < 1e-6);
+
+        // NOTE: The background ray has larger errors than the foreground ray
+        let predicted = Tensor::<Backend, 2>::from_floats(
+            [[0.5, 0.5, 0.5], [1.0, 1.0, 1.0]],
+            &device,
+        )
+        .reshape([1, 2, 3]);
+        let target = Tensor::<Backend, 3>::zeros([1, 2, 3], &device);
+        let mask = |values: [f32; 2]| {
+            Some(
+                Tensor::<Backend, 1>::from_floats(values, &device)
+                    .reshape([1, 2, 1]),
+            )
+        };
+        let loss = |mask: Option<Tensor<Backend, 3>>| {
+            [
+                mse_loss(predicted.clone(), target.clone(), mask.clone()),
+                l1_loss(predicted.clone(), target.clone(), mask),
+            ]
+            .map(|loss| loss.into_scalar())
+        };
+        let [mse, l1] = loss(None);
+        assert!((mse - 0.625).abs() < 1e-6);
+        assert!((l1 - 0.75).abs() < 1e-6);
+        let [mse, l1] = loss(mask([1.0, 0.0]));
+        assert!((mse - 0.25).abs() < 1e-6);
+        assert!((l1 - 0.5).abs() < 1e-6);
+        assert_eq!(loss(mask([0.0, 0.0])), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn sample_pdf_concentration() {
+        let device = Default::default();
+
+        let bins = Tensor::<Backend, 1, Int>::arange(0..9, &device)
+            .float()
+            .reshape([1, 1, 9, 1]);
+
+        let mut weights = [0.0; 9];
+        weights[4] = 1.0;
+        let weights = Tensor::<Backend, 1>::from_floats(weights, &device)
+            .reshape([1, 1, 9, 1]);
+        let distances = sample_pdf(bins.clone(), weights, 16);
+        assert_eq!(distances.dims(), [1, 1, 16, 1]);
+        let distances = distances.into_data().value;
+        assert!(
+            distances.iter().all(|distance| (4.0..=5.0).contains(distance)),
+            "Distances: {:?}",
+            distances
+        );
+        assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        let weights = Tensor::<Backend, 4>::zeros([1, 1, 9, 1], &device);
+        let distances = sample_pdf(bins, weights, 16).into_data().value;
+        for (index, distance) in distances.into_iter().enumerate() {
+            let expected = (index as f32 + 0.5) / 16.0 * 8.0;
+            assert!((distance - expected).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn resample_distances_by_weights() {
+        let device = Default::default();
+
+        let bins = Tensor::<Backend, 1, Int>::arange(0..9, &device)
+            .float()
+            .reshape([1, 1, 9, 1])
+            .repeat(1, 2);
+        let weights = Tensor::<Backend, 1>::from_floats(
+            [[0.0; 9], [0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]]
+                .concat()
+                .as_slice(),
+            &device,
+        )
+        .reshape([1, 2, 9, 1]);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let distances =
+            resample_distances(bins.clone(), weights.clone(), 32, &mut rng);
+        assert_eq!(distances.dims(), [1, 2, 32, 1]);
+
+        let distances = distances.into_data().value;
+        assert!(distances.iter().all(|distance| distance.is_finite()));
+        for ray in distances.chunks_exact(32) {
+            assert!(ray.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert!(ray.iter().all(|distance| (0.0..=8.0).contains(distance)));
+        }
+        assert!(distances[32..64]
+            .iter()
+            .all(|distance| (4.0..=5.0).contains(distance)));
+        assert!(distances[0] < 4.0 && distances[31] > 4.0);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(
+            resample_distances(bins, weights, 32, &mut rng).into_data().value,
+            distances
+        );
+    }
+
+    #[test]
+    fn chunked_renderer_consistency() {
+        let device = Default::default();
+
+        let model = model::SimpleNerfModelConfig::new(16, 4, 6, 2)
+            .init::<Backend>(&device)
+            .unwrap();
+        let positions = Tensor::<Backend, 4>::random(
+            [4, 5, 6, 3],
+            Distribution::Default,
+            &device,
+        );
+        let directions = positions.random_like(Distribution::Default);
+        let distances = Tensor::<Backend, 1, Int>::arange(2..8, &device)
+            .float()
+            .reshape([1, 1, 6, 1])
+            .repeat(0, 4)
+            .repeat(1, 5);
+
+        let outputs = [7, 20].map(|chunk_size| {
+            ChunkedRenderer::new(chunk_size).render(
+                &model,
+                positions.clone(),
+                directions.clone(),
+                distances.clone(),
+            )
+        });
+        let [chunked, unchunked] = outputs;
+        assert_eq!(chunked.rgb.dims(), [4, 5, 3]);
+        assert_eq!(chunked.depth.dims(), [4, 5, 1]);
+        assert_eq!(chunked.weights.dims(), [4, 5, 6, 1]);
+        let unchunked_rgb = unchunked.rgb.into_data();
+        chunked.rgb.into_data().assert_approx_eq(&unchunked_rgb, 5);
+        chunked
+            .depth
+            .into_data()
+            .assert_approx_eq(&unchunked.depth.into_data(), 5);
+        chunked
+            .weights
+            .into_data()
+            .assert_approx_eq(&unchunked.weights.into_data(), 5);
+
+        // NOTE: The unchunked output matches the direct rendering
+        let (densities, colors) = model.forward(positions, directions);
+        volume_render(densities, colors, distances)
+            .rgb
+            .into_data()
+            .assert_approx_eq(&unchunked_rgb, 5);
+    }
+
+    #[test]
+    fn render_view_of_training_pose() {
+        let device = Default::default();
+
+        let config = dataset::SimpleNerfDatasetConfig::new(16, 2.0..6.0);
+        let dataset = config
+            .init_from_file_path::<Autodiff<Backend>>(
+                "resources/lego-tiny/data.npz",
+                &device,
+            )
+            .unwrap();
+        let input = dataset.get(0).unwrap().into_input(&device);
+
+        let training = train::SimpleNerfTrainingConfig::new(
+            model::SimpleNerfModelConfig::new(32, 4, 6, 2),
+        )
+        .with_background(config.background)
+        .with_learning_rate(5e-3);
+        let mut step = training.init::<Autodiff<Backend>>(&device).unwrap();
+        let mut optimizer = AdamConfig::new().init();
+        for seed in 0..20 {
+            let input = input.sample_rays(1024, seed);
+            let batch = dataset::SimpleNerfBatch {
+                depths: None,
+                directions: input.directions.unsqueeze_dim(0),
+                distances: input.distances.unsqueeze_dim(0),
+                images: input.image.unsqueeze_dim(0),
+                intervals: input.intervals.unsqueeze_dim(0),
+                masks: None,
+                positions: input.positions.unsqueeze_dim(0),
+            };
+            let output = TrainStep::step(&step, batch);
+            step = step.optimize(
+                &mut optimizer,
+                training.learning_rate,
+                output.grads,
+            );
+        }
+
+        let image = render_view(
+            &step.model().valid(),
+            dataset.pose_tensor(0).unwrap().inner(),
+            dataset.focal(),
+            dataset.height(),
+            dataset.width(),
+            &config,
+            &device,
+        );
+        assert!(image.is_ok(), "Error: {}", image.unwrap_err());
+
+        let image = image.unwrap();
+        assert_eq!(image.dims(), [100, 100, 3]);
+
+        let other_image = render_view(
+            &step.model().valid(),
+            dataset.pose_tensor(0).unwrap().inner(),
+            dataset.focal(),
+            dataset.height(),
+            dataset.width(),
+            &config
+                .clone()
+                .with_crop(Some(dataset::ImageCrop::Center(0.5)))
+                .with_downscale(2)
+                .with_image_indices(Some(vec![3]))
+                .with_resize_to(Some((50, 50))),
+            &device,
+        );
+        assert!(other_image.is_ok(), "Error: {}", other_image.unwrap_err());
+        assert_eq!(other_image.unwrap().dims(), [100, 100, 3]);
+
+        let psnr = metric::psnr(image, input.image.inner());
+        assert!(psnr > 8.0, "PSNR: {}", psnr);
+    }
+}
@@ -44,7 +44,7 @@ impl<B: Backend> VolumeRenderer<B> {
             Tensor::cat(
                 directions_chunks
                     .into_iter()
-                    .zip(positions_chunks.into_iter())
+                    .zip(positions_chunks)
                     .map(|(directions, positions)| {
                         self.scene.forward(directions, positions)
                     })
@@ -64,39 +64,9 @@ impl<B: Backend> VolumeRenderer<B> {
             scene_outputs.slice(indexs)
         };
 
-        let image = {
-            let translucency = (-densities * intervals).exp();
+        let weights = render::volume_weights(densities, intervals);
 
-            let cumulative_translucency = {
-                let mut cumulative_product = translucency.clone() + 1e-6;
-
-                // NOTE: This is a naive implementation of cumulative product
-                for index in 1..points_per_ray {
-                    let product = cumulative_product.clone().slice([
-                        0..height,
-                        0..width,
-                        index - 1..index,
-                    ]) * cumulative_product.clone().slice([
-                        0..height,
-                        0..width,
-                        index..index + 1,
-                    ]);
-
-                    cumulative_product = cumulative_product.slice_assign(
-                        [0..height, 0..width, index..index + 1],
-                        product,
-                    );
-                }
-
-                cumulative_product
-            };
-
-            let transmittance = (-translucency + 1.0) * cumulative_translucency;
-
-            (colors * transmittance).sum_dim(2).squeeze::<3>(2)
-        };
-
-        image
+        (colors * weights).sum_dim(2).squeeze::<3>(2)
     }
 }
 
@@ -116,8 +86,12 @@ mod tests {
             scene: scene::VolumetricSceneConfig {
                 input_encoder: encoder::PositionalEncoderConfig {
                     encoding_factor: 3,
+                    include_input: true,
                 },
                 hidden_size: 8,
+                direction_encoder: None,
+                layer_count: 8,
+                skip_index: 5,
             },
         }
         .init::<Backend>(&device);
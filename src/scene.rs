@@ -1,11 +1,19 @@
 use crate::*;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use burn::{prelude::*, tensor::activation};
 
 #[derive(Config, Debug)]
 pub struct VolumetricSceneConfig {
     pub hidden_size: usize,
     pub input_encoder: encoder::PositionalEncoderConfig,
+    // NOTE: If it is set, the directions are encoded by it separately
+    // and only affect the colors through the view-dependent head
+    pub direction_encoder: Option<encoder::PositionalEncoderConfig>,
+    #[config(default = 8)]
+    pub layer_count: usize,
+    // NOTE: The encoded inputs are concatenated again before this layer
+    #[config(default = 5)]
+    pub skip_index: usize,
 }
 
 #[derive(Debug, Module)]
@@ -14,6 +22,15 @@ pub struct VolumetricScene<B: Backend> {
     hidden_layers: Vec<nn::Linear<B>>,
     output_layer: nn::Linear<B>,
     skip_indexs: Vec<usize>,
+    view_head: Option<ViewDependentHead<B>>,
+}
+
+#[derive(Debug, Module)]
+pub struct ViewDependentHead<B: Backend> {
+    color_layer: nn::Linear<B>,
+    direction_encoder: encoder::PositionalEncoder<B>,
+    direction_layer: nn::Linear<B>,
+    feature_layer: nn::Linear<B>,
 }
 
 impl VolumetricSceneConfig {
@@ -21,36 +38,71 @@ impl VolumetricSceneConfig {
         &self,
         device: &B::Device,
     ) -> Result<VolumetricScene<B>> {
-        let i = self.input_encoder.get_output_size(6);
+        if self.layer_count == 0 {
+            bail!("Number of layers must be greater than 0");
+        }
+
+        let i = match self.direction_encoder {
+            Some(_) => self.input_encoder.get_output_size(3),
+            None => self.input_encoder.get_output_size(6),
+        };
         let h = self.hidden_size;
-        let o = 3 + 1;
+        let o = match self.direction_encoder {
+            Some(_) => 1,
+            None => 3 + 1,
+        };
+        let skip_indexs = if self.skip_index > 0 {
+            vec![self.skip_index]
+        } else {
+            vec![]
+        };
+
+        let view_head = match self.direction_encoder {
+            Some(ref direction_encoder) => {
+                let d = direction_encoder.get_output_size(3);
+                Some(ViewDependentHead {
+                    color_layer: nn::LinearConfig::new(h / 2, 3).init(device),
+                    direction_encoder: direction_encoder.init(device)?,
+                    direction_layer: nn::LinearConfig::new(h + d, h / 2)
+                        .init(device),
+                    feature_layer: nn::LinearConfig::new(h, h).init(device),
+                })
+            },
+            None => None,
+        };
+
         Ok(VolumetricScene {
             input_encoder: self.input_encoder.init(device)?,
-            hidden_layers: vec![
-                nn::LinearConfig::new(i, h).init(device),
-                nn::LinearConfig::new(h, h).init(device),
-                nn::LinearConfig::new(h, h).init(device),
-                nn::LinearConfig::new(h, h).init(device),
-                nn::LinearConfig::new(h, h).init(device),
-                nn::LinearConfig::new(h + i, h).init(device),
-                nn::LinearConfig::new(h, h).init(device),
-                nn::LinearConfig::new(h, h).init(device),
-            ],
+            hidden_layers: (0..self.layer_count)
+                .map(|index| {
+                    let input_size = match index {
+                        0 => i,
+                        index if skip_indexs.contains(&index) => h + i,
+                        _ => h,
+                    };
+                    nn::LinearConfig::new(input_size, h).init(device)
+                })
+                .collect(),
             output_layer: nn::LinearConfig::new(h, o).init(device),
-            skip_indexs: vec![5],
+            skip_indexs,
+            view_head,
         })
     }
 }
 
 impl<B: Backend> VolumetricScene<B> {
+    // NOTE: The outputs are colors of [N, 0..3] and densities of [N, 3..4]
     pub fn forward(
         &self,
         directions: Tensor<B, 2>,
         positions: Tensor<B, 2>,
     ) -> Tensor<B, 2> {
-        let inputs = self
-            .input_encoder
-            .forward(Tensor::cat(vec![directions, positions], 1));
+        let inputs = match self.view_head {
+            Some(_) => self.input_encoder.forward(positions),
+            None => self
+                .input_encoder
+                .forward(Tensor::cat(vec![directions.clone(), positions], 1)),
+        };
         let mut features = inputs.clone();
 
         for (index, layer) in self.hidden_layers.iter().enumerate() {
@@ -61,17 +113,44 @@ impl<B: Backend> VolumetricScene<B> {
             features = activation::relu(features);
         }
 
-        let outputs = {
-            features = self.output_layer.forward(features);
-            let size = features.dims()[0];
-            let colors =
-                activation::sigmoid(features.clone().slice([0..size, 0..3]));
-            let densities = activation::relu(features.slice([0..size, 3..4]));
+        match self.view_head {
+            Some(ref head) => {
+                let densities = activation::relu(
+                    self.output_layer.forward(features.clone()),
+                );
+                let colors = head.forward(directions, features);
 
-            Tensor::cat(vec![colors, densities], 1)
-        };
+                Tensor::cat(vec![colors, densities], 1)
+            },
+            None => {
+                features = self.output_layer.forward(features);
+                let size = features.dims()[0];
+                let colors = activation::sigmoid(
+                    features.clone().slice([0..size, 0..3]),
+                );
+                let densities =
+                    activation::relu(features.slice([0..size, 3..4]));
+
+                Tensor::cat(vec![colors, densities], 1)
+            },
+        }
+    }
+}
+
+impl<B: Backend> ViewDependentHead<B> {
+    pub fn forward(
+        &self,
+        directions: Tensor<B, 2>,
+        features: Tensor<B, 2>,
+    ) -> Tensor<B, 2> {
+        let directions = self.direction_encoder.forward(directions);
+        let features = self.feature_layer.forward(features);
+        let features = activation::relu(
+            self.direction_layer
+                .forward(Tensor::cat(vec![features, directions], 1)),
+        );
 
-        outputs
+        activation::sigmoid(self.color_layer.forward(features))
     }
 }
 
@@ -88,7 +167,11 @@ mod tests {
             hidden_size: 8,
             input_encoder: encoder::PositionalEncoderConfig {
                 encoding_factor: 1,
+                include_input: true,
             },
+            direction_encoder: None,
+            layer_count: 8,
+            skip_index: 5,
         };
         let device = Default::default();
 
@@ -101,4 +184,34 @@ mod tests {
         let outputs = model.forward(positions, directions);
         assert_eq!(outputs.dims(), [1234, 4]);
     }
+
+    #[test]
+    fn volumetric_scene_view_dependent_head() {
+        let config = VolumetricSceneConfig::new(
+            16,
+            encoder::PositionalEncoderConfig::new(6),
+        )
+        .with_direction_encoder(Some(encoder::PositionalEncoderConfig::new(2)))
+        .with_layer_count(4)
+        .with_skip_index(2);
+        let device = Default::default();
+
+        let model = config.init::<Backend>(&device).unwrap();
+
+        let positions =
+            Tensor::random([1234, 3], Distribution::Default, &device);
+        let directions = positions.random_like(Distribution::Default);
+
+        let outputs = model.forward(directions, positions).into_data().value;
+        assert_eq!(outputs.len(), 1234 * 4);
+        for output in outputs.chunks(4) {
+            assert!(output[..3]
+                .iter()
+                .all(|color| (0.0..=1.0).contains(color)));
+            assert!(output[3] >= 0.0);
+        }
+
+        let config = config.with_layer_count(0);
+        assert!(config.init::<Backend>(&device).is_err());
+    }
 }
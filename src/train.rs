@@ -0,0 +1,163 @@
+use crate::*;
+use anyhow::Result;
+use burn::{
+    prelude::*,
+    tensor::backend::AutodiffBackend,
+    train::{TrainOutput, TrainStep, ValidStep},
+};
+
+// NOTE: The background should be the one of the dataset config,
+// so that the renders agree with the composited training images
+#[derive(Config, Debug)]
+pub struct SimpleNerfTrainingConfig {
+    pub model: model::SimpleNerfModelConfig,
+    #[config(default = "Some([1.0, 1.0, 1.0])")]
+    pub background: Option<[f32; 3]>,
+    #[config(default = 5e-4)]
+    pub learning_rate: f64,
+}
+
+#[derive(Debug, Module)]
+pub struct SimpleNerfTrainingStep<B: Backend> {
+    background: Option<[f32; 3]>,
+    model: model::SimpleNerfModel<B>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimpleNerfTrainingOutput<B: Backend> {
+    pub loss: Tensor<B, 1>,
+    pub psnr: f32,
+}
+
+impl SimpleNerfTrainingConfig {
+    pub fn init<B: Backend>(
+        &self,
+        device: &B::Device,
+    ) -> Result<SimpleNerfTrainingStep<B>> {
+        Ok(SimpleNerfTrainingStep {
+            background: self.background,
+            model: self.model.init(device)?,
+        })
+    }
+}
+
+impl<B: Backend> SimpleNerfTrainingStep<B> {
+    pub fn model(&self) -> &model::SimpleNerfModel<B> {
+        &self.model
+    }
+
+    // NOTE: The masks are not used, so the images should be composited
+    pub fn forward(
+        &self,
+        batch: dataset::SimpleNerfBatch<B>,
+    ) -> SimpleNerfTrainingOutput<B> {
+        let [batch_size, height, width, points_per_ray, _] =
+            batch.positions.dims();
+        let row_count = batch_size * height;
+
+        let (densities, colors) = self.model.forward(
+            batch.positions.reshape([row_count, width, points_per_ray, 3]),
+            batch.directions.reshape([row_count, width, points_per_ray, 3]),
+        );
+        let distances =
+            batch.distances.reshape([row_count, width, points_per_ray, 1]);
+        let output = match self.background {
+            Some(background) => render::volume_render_with_bg(
+                densities, colors, distances, background,
+            ),
+            None => render::volume_render(densities, colors, distances),
+        };
+
+        let loss = render::mse_loss(
+            output.rgb,
+            batch.images.reshape([row_count, width, 3]),
+            None,
+        );
+        let psnr = metric::psnr_from_mse(loss.clone().into_scalar().elem());
+
+        SimpleNerfTrainingOutput {
+            loss,
+            psnr,
+        }
+    }
+}
+
+impl<B: AutodiffBackend>
+    TrainStep<dataset::SimpleNerfBatch<B>, SimpleNerfTrainingOutput<B>>
+    for SimpleNerfTrainingStep<B>
+{
+    fn step(
+        &self,
+        batch: dataset::SimpleNerfBatch<B>,
+    ) -> TrainOutput<SimpleNerfTrainingOutput<B>> {
+        let output = self.forward(batch);
+        let gradients = output.loss.backward();
+        TrainOutput::new(self, gradients, output)
+    }
+}
+
+impl<B: Backend>
+    ValidStep<dataset::SimpleNerfBatch<B>, SimpleNerfTrainingOutput<B>>
+    for SimpleNerfTrainingStep<B>
+{
+    fn step(
+        &self,
+        batch: dataset::SimpleNerfBatch<B>,
+    ) -> SimpleNerfTrainingOutput<B> {
+        self.forward(batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use burn::{backend::Autodiff, data::dataset::Dataset, optim::AdamConfig};
+
+    type Backend = Autodiff<burn::backend::Wgpu>;
+
+    #[test]
+    fn simple_nerf_training_step_loss_decrease() {
+        let device = Default::default();
+
+        let input = dataset::SimpleNerfDatasetConfig::new(16, 2.0..6.0)
+            .init_from_file_path::<Backend>(
+                "resources/lego-tiny/data.npz",
+                &device,
+            )
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .into_input::<Backend>(&device)
+            .sample_rays(1024, 0);
+        let batch = dataset::SimpleNerfBatch {
+            depths: None,
+            directions: input.directions.unsqueeze_dim(0),
+            distances: input.distances.unsqueeze_dim(0),
+            images: input.image.unsqueeze_dim(0),
+            intervals: input.intervals.unsqueeze_dim(0),
+            masks: None,
+            positions: input.positions.unsqueeze_dim(0),
+        };
+
+        let config = SimpleNerfTrainingConfig::new(
+            model::SimpleNerfModelConfig::new(32, 4, 6, 2),
+        );
+        let step = config.init::<Backend>(&device);
+        assert!(step.is_ok(), "Error: {}", step.unwrap_err());
+
+        let mut step = step.unwrap();
+        let mut optimizer = AdamConfig::new().init();
+        let mut losses = vec![];
+        for _ in 0..2 {
+            let output = TrainStep::step(&step, batch.clone());
+            assert!(output.item.psnr.is_finite());
+            losses.push(output.item.loss.into_scalar());
+            step = step.optimize(
+                &mut optimizer,
+                config.learning_rate,
+                output.grads,
+            );
+        }
+        assert!(losses[1] < losses[0], "Losses: {:?}", losses);
+    }
+}